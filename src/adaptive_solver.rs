@@ -1,33 +1,243 @@
 use std::{rc::Rc, time::Instant};
 
 use crate::{
-    genetic::GeneticAlgorithm,
+    ant_colony::{AntColony, AntSystem},
+    construction::Construction,
+    crossover::Crossover,
+    genetic::{DEFAULT_SEED, GeneticAlgorithm},
+    iterated_local_search::{Acceptance, IteratedLocalSearch},
+    local_search::LocalSearch,
+    mutation::Mutation,
     problem::{Problem, Solution},
+    replacement::ReplacementStrategy,
+    selection::SelectionStrategy,
+    simulated_annealing::{CoolingSchedule, SimulatedAnnealing},
+    tabu_search::TabuSearch,
 };
 
-pub fn adaptive_solve(problem: Rc<Problem>, timer: &Instant) -> Solution {
-    let num_cities = problem.cities.len();
-    let population_size: usize = 200.max(num_cities);
-    let extra_population_size: usize = 200.max(num_cities);
+/// Up to this size the genetic algorithm, with its children polished by local search,
+/// finds tours at least as short as iterated local search within the class time limits.
+pub const GENETIC_ALGORITHM_LIMIT: usize = 500;
+
+/// From this size on, even a greedy start tour takes a noticeable share of the time
+/// limit, so iterated local search starts from a space-filling curve instead.
+pub const SPACE_FILLING_CURVE_THRESHOLD: usize = 100_000;
+
+/// The time budget for an instance of `num_cities` cities, including a safety margin.
+pub fn class_time_limit(num_cities: usize) -> u64 {
     const MARGIN: u64 = 2;
     const CLASS_1_TIME_LIMIT: u64 = 60;
     const CLASS_2_TIME_LIMIT: u64 = 75;
     const CLASS_3_TIME_LIMIT: u64 = 120;
     const CLASS_4_TIME_LIMIT: u64 = 300;
-    let solution = match num_cities {
-        0..10 => Solution::from_brute_force(&problem),
-        10..50 => GeneticAlgorithm::new(problem, population_size, extra_population_size)
-            .solve(timer, CLASS_1_TIME_LIMIT - MARGIN),
-        50..100 => GeneticAlgorithm::new(problem, population_size, extra_population_size)
-            .solve(timer, CLASS_1_TIME_LIMIT - MARGIN),
-        100..200 => GeneticAlgorithm::new(problem, population_size, extra_population_size)
-            .solve(timer, CLASS_2_TIME_LIMIT - MARGIN),
-        200..500 => GeneticAlgorithm::new(problem, population_size, extra_population_size)
-            .solve(timer, CLASS_3_TIME_LIMIT - MARGIN),
-        500.. => GeneticAlgorithm::new(problem, population_size, extra_population_size)
-            .solve(timer, CLASS_4_TIME_LIMIT - MARGIN),
+    let time_limit = match num_cities {
+        0..100 => CLASS_1_TIME_LIMIT,
+        100..200 => CLASS_2_TIME_LIMIT,
+        200..500 => CLASS_3_TIME_LIMIT,
+        500.. => CLASS_4_TIME_LIMIT,
     };
-    solution
+    time_limit - MARGIN
+}
+
+/// Settings shared by every solver that [`adaptive_solve_with`] can pick.
+pub struct SolveOptions {
+    /// Overrides the time limit of the size class, in seconds since `timer` started.
+    pub time_limit_secs: Option<u64>,
+    pub seed: u64,
+    /// Tours to start from, e.g. the result of a previous run.
+    pub initial_solutions: Vec<Solution>,
+    /// Heuristics that build the initial population of the genetic algorithm, or the
+    /// start tour of the other solvers. Empty keeps the solver's own.
+    pub constructions: Vec<Construction>,
+    /// Local searches that polish the constructed tours. Iterated local search and tabu
+    /// search keep their own when this is empty.
+    pub local_searches: Vec<LocalSearch>,
+    /// Local searches that improve the children of the genetic algorithm.
+    pub offspring_local_searches: Vec<LocalSearch>,
+    /// The share of children that the offspring local searches improve.
+    pub offspring_local_search_probability: f64,
+    /// The crossovers of the genetic algorithm with their weights. Empty keeps its own mix.
+    pub crossovers: Vec<(Box<dyn Crossover>, f64)>,
+    /// The mutations of the genetic algorithm with the share of children each one changes.
+    pub mutations: Vec<(Mutation, f64)>,
+    /// How the genetic algorithm picks parents. `None` keeps its own strategy.
+    pub selection: Option<Rc<dyn SelectionStrategy>>,
+    /// How the genetic algorithm picks the survivors of a generation. `None` keeps its
+    /// own strategy.
+    pub replacement: Option<Box<dyn ReplacementStrategy>>,
+    /// When iterated local search moves on to a tour found after a kick.
+    pub acceptance: Acceptance,
+    /// How simulated annealing lowers the temperature.
+    pub cooling_schedule: CoolingSchedule,
+    /// How many times simulated annealing raises the temperature again.
+    pub reheats: u32,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        Self {
+            time_limit_secs: None,
+            seed: DEFAULT_SEED,
+            initial_solutions: Vec::new(),
+            constructions: Vec::new(),
+            local_searches: Vec::new(),
+            offspring_local_searches: Vec::new(),
+            offspring_local_search_probability: 1.0,
+            crossovers: Vec::new(),
+            mutations: Vec::new(),
+            selection: None,
+            replacement: None,
+            acceptance: Acceptance::BetterOrEqual,
+            cooling_schedule: CoolingSchedule::Geometric,
+            reheats: 0,
+        }
+    }
+}
+
+pub fn adaptive_solve(problem: Rc<Problem>, timer: &Instant) -> Solution {
+    adaptive_solve_with(problem, timer, SolveOptions::default())
+}
+
+pub fn adaptive_solve_with(
+    problem: Rc<Problem>,
+    timer: &Instant,
+    options: SolveOptions,
+) -> Solution {
+    let num_cities = problem.cities.len();
+    let time_limit_secs = options
+        .time_limit_secs
+        .unwrap_or_else(|| class_time_limit(num_cities));
+    match num_cities {
+        0..10 => Solution::from_brute_force(&problem),
+        10..GENETIC_ALGORITHM_LIMIT => {
+            let mut options = options;
+            if options.local_searches.is_empty() {
+                options.local_searches = vec![LocalSearch::TwoOpt, LocalSearch::OrOpt];
+            }
+            if options.offspring_local_searches.is_empty() {
+                options.offspring_local_searches = options.local_searches.clone();
+            }
+            genetic_algorithm(problem, timer, time_limit_secs, options)
+        }
+        GENETIC_ALGORITHM_LIMIT..SPACE_FILLING_CURVE_THRESHOLD => {
+            iterated_local_search(problem, timer, time_limit_secs, options)
+        }
+        SPACE_FILLING_CURVE_THRESHOLD.. => {
+            let mut options = options;
+            if options.constructions.is_empty() {
+                options.constructions = vec![Construction::HilbertCurve];
+            }
+            iterated_local_search(problem, timer, time_limit_secs, options)
+        }
+    }
+}
+
+/// The population size of the genetic algorithm: a tour per city, but at least 200.
+pub fn population_size(num_cities: usize) -> usize {
+    200.max(num_cities)
+}
+
+/// The genetic algorithm with the operators of `options`.
+pub fn genetic_algorithm(
+    problem: Rc<Problem>,
+    timer: &Instant,
+    time_limit_secs: u64,
+    options: SolveOptions,
+) -> Solution {
+    let population_size = population_size(problem.cities.len());
+    let mut genetic_algorithm =
+        GeneticAlgorithm::with_seed(problem, population_size, population_size, options.seed);
+    genetic_algorithm.initial_solutions = options.initial_solutions;
+    if !options.constructions.is_empty() {
+        genetic_algorithm.constructions = options.constructions;
+    }
+    genetic_algorithm.local_searches = options.local_searches;
+    genetic_algorithm.offspring_local_searches = options.offspring_local_searches;
+    genetic_algorithm.offspring_local_search_probability =
+        options.offspring_local_search_probability;
+    if !options.crossovers.is_empty() {
+        genetic_algorithm.crossovers = options.crossovers;
+    }
+    genetic_algorithm.mutations = options.mutations;
+    if let Some(selection) = options.selection {
+        genetic_algorithm.selection = selection;
+    }
+    if let Some(replacement) = options.replacement {
+        genetic_algorithm.replacement = replacement;
+    }
+    genetic_algorithm.solve(timer, time_limit_secs)
+}
+
+/// Iterated local search from the start tour and with the local searches of `options`.
+pub fn iterated_local_search(
+    problem: Rc<Problem>,
+    timer: &Instant,
+    time_limit_secs: u64,
+    options: SolveOptions,
+) -> Solution {
+    let mut iterated_local_search = IteratedLocalSearch::with_seed(problem, options.seed);
+    iterated_local_search.initial_solutions = options.initial_solutions;
+    if !options.constructions.is_empty() {
+        iterated_local_search.constructions = options.constructions;
+    }
+    if !options.local_searches.is_empty() {
+        iterated_local_search.local_searches = options.local_searches;
+    }
+    iterated_local_search.acceptance = options.acceptance;
+    iterated_local_search.solve(timer, time_limit_secs)
+}
+
+/// Simulated annealing from the start tour and with the schedule of `options`.
+pub fn simulated_annealing(
+    problem: Rc<Problem>,
+    timer: &Instant,
+    time_limit_secs: u64,
+    options: SolveOptions,
+) -> Solution {
+    let mut simulated_annealing = SimulatedAnnealing::with_seed(problem, options.seed);
+    simulated_annealing.initial_solutions = options.initial_solutions;
+    if !options.constructions.is_empty() {
+        simulated_annealing.constructions = options.constructions;
+    }
+    simulated_annealing.cooling_schedule = options.cooling_schedule;
+    simulated_annealing.reheats = options.reheats;
+    simulated_annealing.solve(timer, time_limit_secs)
+}
+
+/// An ant colony with the given pheromone rules, seeded with the start tours of
+/// `options`, whose local searches polish the best ant of every generation.
+pub fn ant_colony(
+    problem: Rc<Problem>,
+    timer: &Instant,
+    time_limit_secs: u64,
+    ant_system: AntSystem,
+    options: SolveOptions,
+) -> Solution {
+    let mut ant_colony = AntColony::with_seed(problem, ant_system, options.seed);
+    ant_colony.initial_solutions = options.initial_solutions;
+    if !options.constructions.is_empty() {
+        ant_colony.constructions = options.constructions;
+    }
+    ant_colony.local_searches = options.local_searches;
+    ant_colony.solve(timer, time_limit_secs)
+}
+
+/// Tabu search from the start tour of `options`, whose local searches polish every start.
+pub fn tabu_search(
+    problem: Rc<Problem>,
+    timer: &Instant,
+    time_limit_secs: u64,
+    options: SolveOptions,
+) -> Solution {
+    let mut tabu_search = TabuSearch::with_seed(problem, options.seed);
+    tabu_search.initial_solutions = options.initial_solutions;
+    if !options.constructions.is_empty() {
+        tabu_search.constructions = options.constructions;
+    }
+    if !options.local_searches.is_empty() {
+        tabu_search.local_searches = options.local_searches;
+    }
+    tabu_search.solve(timer, time_limit_secs)
 }
 
 // brute force, genetic, nearest neighbor, nearest neighbor with different starting points,
@@ -0,0 +1,326 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    construction::{Construction, build_start_tour},
+    genetic::DEFAULT_SEED,
+    local_search::{LOCAL_SEARCH_NEIGHBORS, LocalSearch, deadline_passed, distance},
+    neighbors::{Grid, UnvisitedCities, nearest_neighbor_lists},
+    problem::{Problem, Solution},
+};
+
+/// How many nearest neighbors of every city carry pheromone. The ants only leave the
+/// candidate lists when every candidate has been visited.
+pub const ANT_COLONY_NEIGHBORS: usize = LOCAL_SEARCH_NEIGHBORS;
+/// How much more the inverse edge length counts than the pheromone.
+const BETA: i32 = 2;
+/// How often an Ant Colony System ant takes its best candidate instead of sampling.
+const ACS_EXPLOITATION: f64 = 0.9;
+/// The evaporation rate of Ant Colony System, both globally and on the edges ants use.
+const ACS_EVAPORATION: f64 = 0.1;
+/// The evaporation rate of MAX-MIN Ant System.
+const MMAS_EVAPORATION: f64 = 0.02;
+/// How likely a MAX-MIN ant is to rebuild the best tour once the pheromone converged,
+/// which sets the lower pheromone limit.
+const MMAS_BEST_TOUR_PROBABILITY: f64 = 0.05;
+/// Every this many generations MAX-MIN Ant System reinforces the best tour so far
+/// instead of the best tour of the generation.
+const MMAS_BEST_SO_FAR_PERIOD: u64 = 5;
+/// How many cities an ant visits between two looks at the clock.
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+
+/// The pheromone rules of the colony.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AntSystem {
+    /// Ant Colony System: ants mostly take their best candidate, edges lose pheromone as
+    /// ants use them, and only the best tour so far gains pheromone.
+    AntColonySystem,
+    /// MAX-MIN Ant System: ants always sample, all pheromone evaporates, one tour per
+    /// generation gains pheromone, and the pheromone is kept between two limits.
+    MaxMinAntSystem,
+}
+
+/// Ant colony optimisation with pheromone only on the edges to the `ANT_COLONY_NEIGHBORS`
+/// nearest neighbors of every city, so memory and time per ant stay linear in the number
+/// of cities. The ants of a generation build their tours in parallel, and the best of
+/// them can be polished by local search before the pheromone update.
+pub struct AntColony {
+    pub problem: Rc<Problem>,
+    pub ant_system: AntSystem,
+    pub num_ants: usize,
+    /// Tours to start from, e.g. the result of a previous run. The best of them and of the
+    /// constructed tours is the first best tour and sets the initial pheromone.
+    pub initial_solutions: Vec<Solution>,
+    /// Heuristics that build reference tours from the first city.
+    pub constructions: Vec<Construction>,
+    /// Local searches that polish the best tour of every generation.
+    pub local_searches: Vec<LocalSearch>,
+    rng: RefCell<StdRng>,
+}
+
+/// The pheromone and attractiveness of the candidate edges, where slot `i * k + s`
+/// belongs to the edge from city `i` to its `s`-th candidate.
+struct Trails<'a> {
+    problem: &'a Problem,
+    neighbor_lists: &'a [Vec<u32>],
+    k: usize,
+    pheromone: Vec<f64>,
+    /// `pheromone * (1 / length)^BETA`, refreshed before every generation.
+    attractiveness: Vec<f64>,
+}
+
+impl Trails<'_> {
+    fn refresh_attractiveness(&mut self) {
+        for (city, list) in self.neighbor_lists.iter().enumerate() {
+            for (slot, &other) in list.iter().enumerate() {
+                let index = city * self.k + slot;
+                let length = distance(self.problem, city as u32, other).max(f64::MIN_POSITIVE);
+                self.attractiveness[index] = self.pheromone[index] / length.powi(BETA);
+            }
+        }
+    }
+
+    /// Applies `update` to the pheromone of the edge `(a, b)` in the candidate lists of
+    /// both ends, where it is a candidate.
+    fn update_edge(&mut self, a: u32, b: u32, update: impl Fn(f64) -> f64) {
+        for (from, to) in [(a, b), (b, a)] {
+            let list = &self.neighbor_lists[from as usize];
+            if let Some(slot) = list.iter().position(|&other| other == to) {
+                let index = from as usize * self.k + slot;
+                self.pheromone[index] = update(self.pheromone[index]);
+            }
+        }
+    }
+
+    fn update_tour(&mut self, order: &[u32], update: impl Fn(f64) -> f64) {
+        for (i, &city) in order.iter().enumerate() {
+            let next = order[(i + 1) % order.len()];
+            self.update_edge(city, next, &update);
+        }
+    }
+}
+
+/// Builds one ant's tour from a random city: with probability `exploitation` the ant takes
+/// its most attractive unvisited candidate, otherwise it samples one in proportion to the
+/// attractiveness, and it moves to the nearest unvisited city when no candidate is left.
+/// The ant gives up when `deadline` passes, since a tour of a big instance takes seconds.
+fn build_tour(
+    trails: &Trails,
+    grid: &Grid,
+    exploitation: f64,
+    deadline: Instant,
+    rng: &mut impl Rng,
+) -> Option<(Vec<u32>, f64)> {
+    let cities = &trails.problem.cities;
+    let mut unvisited = UnvisitedCities::new(grid, cities);
+    let first = rng.random_range(0..cities.len()) as u32;
+    let mut order = Vec::with_capacity(cities.len());
+    let mut length = 0.0;
+    let mut city = first;
+    unvisited.visit(city);
+    order.push(city);
+    let mut weights = Vec::with_capacity(trails.k);
+    while order.len() < cities.len() {
+        if order.len() % DEADLINE_CHECK_INTERVAL == 0 && deadline_passed(Some(deadline)) {
+            return None;
+        }
+        weights.clear();
+        let base = city as usize * trails.k;
+        for (slot, &other) in trails.neighbor_lists[city as usize].iter().enumerate() {
+            if !unvisited.is_visited(other) {
+                weights.push((other, trails.attractiveness[base + slot]));
+            }
+        }
+        let next = if weights.is_empty() {
+            unvisited.nearest(city).unwrap()
+        } else if rng.random::<f64>() < exploitation {
+            weights.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap().0
+        } else {
+            let total: f64 = weights.iter().map(|&(_, weight)| weight).sum();
+            let mut threshold = rng.random::<f64>() * total;
+            let mut chosen = weights[weights.len() - 1].0;
+            for &(other, weight) in &weights {
+                if threshold < weight {
+                    chosen = other;
+                    break;
+                }
+                threshold -= weight;
+            }
+            chosen
+        };
+        length += distance(trails.problem, city, next);
+        unvisited.visit(next);
+        order.push(next);
+        city = next;
+    }
+    length += distance(trails.problem, city, first);
+    Some((order, length))
+}
+
+impl AntColony {
+    pub fn new(problem: Rc<Problem>, ant_system: AntSystem) -> Self {
+        Self::with_seed(problem, ant_system, DEFAULT_SEED)
+    }
+
+    pub fn with_seed(problem: Rc<Problem>, ant_system: AntSystem, seed: u64) -> Self {
+        Self {
+            problem,
+            ant_system,
+            num_ants: 10,
+            initial_solutions: Vec::new(),
+            constructions: vec![Construction::NearestNeighbor],
+            local_searches: Vec::new(),
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// Runs generations of ants until `time_limit_secs` seconds after `timer` started, and
+    /// returns the best tour found.
+    pub fn solve(&self, timer: &Instant, time_limit_secs: u64) -> Solution {
+        let deadline = *timer + Duration::from_secs(time_limit_secs);
+        let problem = &self.problem;
+        let num_cities = problem.cities.len();
+        let mut best = build_start_tour(
+            problem,
+            &self.constructions,
+            &self.initial_solutions,
+            deadline,
+        );
+        if num_cities < 4 {
+            return best;
+        }
+        let neighbor_lists = nearest_neighbor_lists(problem, ANT_COLONY_NEIGHBORS);
+        let k = neighbor_lists[0].len();
+        let grid = Grid::new(&problem.cities);
+        let reference_length = best.total_distance() as f64;
+        let (initial_pheromone, exploitation, evaporation) = match self.ant_system {
+            AntSystem::AntColonySystem => (
+                1.0 / (num_cities as f64 * reference_length),
+                ACS_EXPLOITATION,
+                ACS_EVAPORATION,
+            ),
+            AntSystem::MaxMinAntSystem => (
+                1.0 / (MMAS_EVAPORATION * reference_length),
+                0.0,
+                MMAS_EVAPORATION,
+            ),
+        };
+        let mut trails = Trails {
+            problem,
+            neighbor_lists: &neighbor_lists,
+            k,
+            pheromone: vec![initial_pheromone; num_cities * k],
+            attractiveness: vec![0.0; num_cities * k],
+        };
+        let threads = thread::available_parallelism()
+            .map_or(1, |threads| threads.get())
+            .min(self.num_ants.max(1));
+        let mut rng = self.rng.borrow_mut();
+        let mut best_order = best.order_without_loop.clone();
+        let mut best_length = reference_length;
+        for generation in 0.. {
+            if deadline_passed(Some(deadline)) {
+                break;
+            }
+            trails.refresh_attractiveness();
+            let seeds = (0..self.num_ants.max(1))
+                .map(|_| rng.random::<u64>())
+                .collect::<Vec<_>>();
+            let chunk_size = seeds.len().div_ceil(threads);
+            let ants = thread::scope(|scope| {
+                let handles = seeds
+                    .chunks(chunk_size)
+                    .map(|seeds| {
+                        let (trails, grid) = (&trails, &grid);
+                        scope.spawn(move || {
+                            seeds
+                                .iter()
+                                .map(|&seed| {
+                                    let mut rng = StdRng::seed_from_u64(seed);
+                                    build_tour(trails, grid, exploitation, deadline, &mut rng)
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .flat_map(|handle| handle.join().unwrap())
+                    .collect::<Option<Vec<_>>>()
+            });
+            let Some(ants) = ants else {
+                break;
+            };
+            let (mut generation_best, mut generation_length) = ants
+                .iter()
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .cloned()
+                .unwrap();
+            if !self.local_searches.is_empty() {
+                let polished = Solution::new(
+                    generation_best,
+                    Rc::downgrade(problem),
+                    Some(generation_length as f32),
+                )
+                .improve_with(
+                    &self.local_searches,
+                    &neighbor_lists,
+                    Some(deadline),
+                );
+                generation_length = polished.total_distance() as f64;
+                generation_best = polished.order_without_loop;
+            }
+            if generation_length < best_length {
+                best_length = generation_length;
+                best_order.clone_from(&generation_best);
+            }
+            match self.ant_system {
+                AntSystem::AntColonySystem => {
+                    // the local update, applied after the generation since the ants share
+                    // the trails while they build their tours in parallel
+                    for (order, _) in &ants {
+                        trails.update_tour(order, |pheromone| {
+                            (1.0 - evaporation) * pheromone + evaporation * initial_pheromone
+                        });
+                    }
+                    let deposit = evaporation / best_length;
+                    trails.update_tour(&best_order, |pheromone| {
+                        (1.0 - evaporation) * pheromone + deposit
+                    });
+                }
+                AntSystem::MaxMinAntSystem => {
+                    let max_pheromone = 1.0 / (evaporation * best_length);
+                    let root = MMAS_BEST_TOUR_PROBABILITY.powf(1.0 / num_cities as f64);
+                    // on tiny instances the formula would exceed the upper limit
+                    let min_pheromone = (max_pheromone * (1.0 - root)
+                        / ((k as f64 / 2.0 - 1.0).max(1.0) * root))
+                        .min(max_pheromone);
+                    for pheromone in &mut trails.pheromone {
+                        *pheromone *= 1.0 - evaporation;
+                    }
+                    let (order, length) = if generation % MMAS_BEST_SO_FAR_PERIOD == 0 {
+                        (&best_order, best_length)
+                    } else {
+                        (&generation_best, generation_length)
+                    };
+                    trails.update_tour(order, |pheromone| pheromone + 1.0 / length);
+                    for pheromone in &mut trails.pheromone {
+                        *pheromone = pheromone.clamp(min_pheromone, max_pheromone);
+                    }
+                }
+            }
+        }
+        if best_length < best.total_distance() as f64 {
+            let position = best_order.iter().position(|&city| city == 0).unwrap();
+            best_order.rotate_left(position);
+            best = Solution::new(best_order, Rc::downgrade(problem), None);
+        }
+        best
+    }
+}
@@ -0,0 +1,256 @@
+use std::rc::Rc;
+
+use ordered_float::NotNan;
+
+use crate::{
+    greedy_edge::Fragments,
+    neighbors::{edge_length, nearest_neighbor_lists},
+    problem::{Problem, Solution},
+};
+
+/// Up to this many cities the spanning tree is computed exactly with Prim's algorithm
+/// in O(n²); above it only the nearest-neighbor candidate edges are considered.
+pub const EXACT_SPANNING_TREE_LIMIT: usize = 10_000;
+
+/// Up to this many odd-degree vertices the matching is solved exactly by dynamic
+/// programming over subsets; above it the matching is greedy.
+pub const EXACT_MATCHING_LIMIT: usize = 18;
+
+/// How many nearest neighbors give the candidate edges of the large-instance spanning
+/// tree and matching.
+pub const CHRISTOFIDES_NEIGHBORS: usize = 10;
+
+/// Prim's algorithm on the complete graph.
+fn exact_spanning_tree(problem: &Problem) -> Vec<(u32, u32)> {
+    let num_cities = problem.cities.len();
+    let mut in_tree = vec![false; num_cities];
+    let mut best_distance = vec![f32::INFINITY; num_cities];
+    let mut best_parent = vec![0u32; num_cities];
+    let mut edges = Vec::with_capacity(num_cities.saturating_sub(1));
+    let mut current = 0;
+    for _ in 1..num_cities {
+        in_tree[current] = true;
+        let mut next = usize::MAX;
+        let mut next_distance = f32::INFINITY;
+        for city in 0..num_cities {
+            if in_tree[city] {
+                continue;
+            }
+            let distance = edge_length(problem, current as u32, city as u32);
+            if distance < best_distance[city] {
+                best_distance[city] = distance;
+                best_parent[city] = current as u32;
+            }
+            if best_distance[city] < next_distance || next == usize::MAX {
+                next_distance = best_distance[city];
+                next = city;
+            }
+        }
+        edges.push((best_parent[next], next as u32));
+        current = next;
+    }
+    edges
+}
+
+/// Kruskal's algorithm on the nearest-neighbor candidate edges. If they leave the cities
+/// in several components, each extra component is joined through the shortest edge from
+/// one of its cities to another component.
+fn candidate_spanning_tree(problem: &Problem) -> Vec<(u32, u32)> {
+    let num_cities = problem.cities.len();
+    let neighbor_lists = nearest_neighbor_lists(problem, CHRISTOFIDES_NEIGHBORS);
+    let mut candidate_edges = Vec::new();
+    for (a, neighbors) in neighbor_lists.iter().enumerate() {
+        for &b in neighbors {
+            let length = NotNan::new(edge_length(problem, a as u32, b)).unwrap();
+            candidate_edges.push((length, (a as u32).min(b), (a as u32).max(b)));
+        }
+    }
+    candidate_edges.sort_unstable();
+    candidate_edges.dedup();
+
+    let mut components = Fragments::new(num_cities);
+    let mut edges = Vec::with_capacity(num_cities - 1);
+    for (_, a, b) in candidate_edges {
+        if components.find(a) != components.find(b) {
+            components.union(a, b);
+            edges.push((a, b));
+        }
+    }
+    while edges.len() + 1 < num_cities {
+        let root = components.find(0);
+        let representative = (0..num_cities as u32)
+            .find(|&city| components.find(city) != root)
+            .unwrap();
+        let representative_root = components.find(representative);
+        let nearest = (0..num_cities as u32)
+            .filter(|&city| components.find(city) != representative_root)
+            .min_by_key(|&city| NotNan::new(edge_length(problem, representative, city)).unwrap())
+            .unwrap();
+        components.union(representative, nearest);
+        edges.push((representative, nearest));
+    }
+    edges
+}
+
+/// Minimum-weight perfect matching by dynamic programming over subsets: the lowest
+/// unmatched vertex of every subset is paired with each of the others in turn.
+fn exact_matching(problem: &Problem, odd: &[u32]) -> Vec<(u32, u32)> {
+    let num_odd = odd.len();
+    let full = (1usize << num_odd) - 1;
+    let mut cost = vec![f32::INFINITY; full + 1];
+    let mut partner = vec![(0u8, 0u8); full + 1];
+    cost[0] = 0.0;
+    for matched in 0..full {
+        if cost[matched].is_infinite() {
+            continue;
+        }
+        let i = (!matched).trailing_zeros() as usize;
+        for j in i + 1..num_odd {
+            if matched & (1 << j) != 0 {
+                continue;
+            }
+            let next = matched | (1 << i) | (1 << j);
+            let next_cost = cost[matched] + edge_length(problem, odd[i], odd[j]);
+            if next_cost < cost[next] {
+                cost[next] = next_cost;
+                partner[next] = (i as u8, j as u8);
+            }
+        }
+    }
+    let mut pairs = Vec::with_capacity(num_odd / 2);
+    let mut matched = full;
+    while matched != 0 {
+        let (i, j) = partner[matched];
+        pairs.push((odd[i as usize], odd[j as usize]));
+        matched &= !(1 << i) & !(1 << j);
+    }
+    pairs
+}
+
+/// Matches the closest candidate pairs first. Vertices whose candidates were all taken
+/// are paired with their nearest unmatched vertex afterwards.
+fn greedy_matching(problem: &Problem, odd: &[u32]) -> Vec<(u32, u32)> {
+    let odd_problem = Problem::new(
+        odd.iter()
+            .map(|&city| problem.cities[city as usize])
+            .collect(),
+    );
+    let neighbor_lists = nearest_neighbor_lists(&odd_problem, CHRISTOFIDES_NEIGHBORS);
+    let mut candidate_pairs = Vec::new();
+    for (a, neighbors) in neighbor_lists.iter().enumerate() {
+        for &b in neighbors {
+            let length = NotNan::new(edge_length(&odd_problem, a as u32, b)).unwrap();
+            candidate_pairs.push((length, (a as u32).min(b), (a as u32).max(b)));
+        }
+    }
+    candidate_pairs.sort_unstable();
+
+    let mut is_matched = vec![false; odd.len()];
+    let mut pairs = Vec::with_capacity(odd.len() / 2);
+    for (_, a, b) in candidate_pairs {
+        if !is_matched[a as usize] && !is_matched[b as usize] {
+            is_matched[a as usize] = true;
+            is_matched[b as usize] = true;
+            pairs.push((odd[a as usize], odd[b as usize]));
+        }
+    }
+    let mut unmatched = (0..odd.len() as u32)
+        .filter(|&a| !is_matched[a as usize])
+        .collect::<Vec<_>>();
+    while let Some(a) = unmatched.pop() {
+        let (position, &b) = unmatched
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, &b)| NotNan::new(edge_length(&odd_problem, a, b)).unwrap())
+            .unwrap();
+        unmatched.swap_remove(position);
+        pairs.push((odd[a as usize], odd[b as usize]));
+    }
+    pairs
+}
+
+/// Hierholzer's algorithm on the multigraph given by `edges`, in which every vertex has
+/// even degree. Returns the vertices of the closed walk starting at `start`.
+fn euler_circuit(num_cities: usize, edges: &[(u32, u32)], start: u32) -> Vec<u32> {
+    // incident edges of every vertex in compressed rows
+    let mut row_start = vec![0usize; num_cities + 1];
+    for &(a, b) in edges {
+        row_start[a as usize + 1] += 1;
+        row_start[b as usize + 1] += 1;
+    }
+    for city in 0..num_cities {
+        row_start[city + 1] += row_start[city];
+    }
+    let mut incident = vec![0usize; row_start[num_cities]];
+    let mut fill = row_start.clone();
+    for (edge, &(a, b)) in edges.iter().enumerate() {
+        incident[fill[a as usize]] = edge;
+        fill[a as usize] += 1;
+        incident[fill[b as usize]] = edge;
+        fill[b as usize] += 1;
+    }
+
+    let mut used = vec![false; edges.len()];
+    let mut next_incident = row_start[..num_cities].to_vec();
+    let mut stack = vec![start];
+    let mut circuit = Vec::with_capacity(edges.len() + 1);
+    while let Some(&city) = stack.last() {
+        let city = city as usize;
+        while next_incident[city] < row_start[city + 1] && used[incident[next_incident[city]]] {
+            next_incident[city] += 1;
+        }
+        if next_incident[city] == row_start[city + 1] {
+            circuit.push(stack.pop().unwrap());
+        } else {
+            let edge = incident[next_incident[city]];
+            used[edge] = true;
+            let (a, b) = edges[edge];
+            stack.push(if a as usize == city { b } else { a });
+        }
+    }
+    circuit.reverse();
+    circuit
+}
+
+impl Solution {
+    /// Christofides' construction: a minimum spanning tree, plus a minimum-weight perfect
+    /// matching of its odd-degree vertices, gives an Eulerian multigraph whose Euler
+    /// circuit is shortcut to a tour starting at `start_index`.
+    ///
+    /// With the exact tree and matching the tour is at most 1.5 times the optimum. Past
+    /// `EXACT_SPANNING_TREE_LIMIT` cities the tree is built from candidate edges and past
+    /// `EXACT_MATCHING_LIMIT` odd vertices the matching is greedy, which keeps the running
+    /// time near O(n log n) but loses the guarantee.
+    pub fn from_christofides(problem: &Rc<Problem>, start_index: usize) -> Self {
+        let num_cities = problem.cities.len();
+        assert!(start_index < num_cities);
+        let mut edges = if num_cities <= EXACT_SPANNING_TREE_LIMIT {
+            exact_spanning_tree(problem)
+        } else {
+            candidate_spanning_tree(problem)
+        };
+
+        let mut degree = vec![0u32; num_cities];
+        for &(a, b) in &edges {
+            degree[a as usize] += 1;
+            degree[b as usize] += 1;
+        }
+        let odd = (0..num_cities as u32)
+            .filter(|&city| degree[city as usize] % 2 == 1)
+            .collect::<Vec<_>>();
+        if odd.len() <= EXACT_MATCHING_LIMIT {
+            edges.extend(exact_matching(problem, &odd));
+        } else {
+            edges.extend(greedy_matching(problem, &odd));
+        }
+
+        // shortcut: keep the first visit of every city
+        let mut visited = vec![false; num_cities];
+        let order = euler_circuit(num_cities, &edges, start_index as u32)
+            .into_iter()
+            .filter(|&city| !std::mem::replace(&mut visited[city as usize], true))
+            .collect::<Vec<_>>();
+        assert_eq!(order.len(), num_cities);
+        Solution::new(order, Rc::downgrade(problem), None)
+    }
+}
@@ -66,6 +66,12 @@ pub struct CoverTree<T: Ord + Clone + Distance + std::fmt::Debug> {
     root: Option<Rc<CoverTreeNode<T>>>,
 }
 
+impl<T: Ord + Clone + Distance + std::fmt::Debug> Default for CoverTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
     pub fn new() -> Self {
         Self { root: None }
@@ -89,7 +95,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
         // it is always possible to find a parent in current_potential_parents that is a valid parent for the new point
         let mut level_to_potential_parents: BTreeMap<i32, Vec<Rc<CoverTreeNode<T>>>> =
             BTreeMap::new();
-        let root_level = root.level.borrow().clone();
+        let root_level = *root.level.borrow();
         level_to_potential_parents.insert(root_level, vec![root.clone()]);
         // populates the potential parents at each level, until there is no more potential parents to explore
         for i in (-root_level..).map(|x| -x) {
@@ -108,7 +114,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
                 }
                 let children = parent.non_self_descendants.borrow();
                 for child in children.iter() {
-                    let child_level = child.level.borrow().clone();
+                    let child_level = *child.level.borrow();
                     if child_level == i - 1 {
                         // needs to be pushed to the next potential parents if it satisfies the cover constraint
                         let distance = child.point.distance(&p);
@@ -147,9 +153,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
 
     pub fn nearest_neighbor(&self, query: &T) -> Option<(T, u32, f32)> {
         // retrieve the root node, if there is no root node, return None
-        let Some(root) = self.root.as_ref() else {
-            return None;
-        };
+        let root = self.root.as_ref()?;
         let mut best_distance = root.point.distance(query);
         // let mut best_candidates: BinaryHeap<(NotNan<f32>, RcKey<CoverTreeNode<T>>)> =
         //     BinaryHeap::new();
@@ -158,7 +162,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
         //     RcKey::new(root.clone()),
         // ));
         let mut best_candidate = root.clone();
-        let root_level = root.level.borrow().clone();
+        let root_level = *root.level.borrow();
         let mut current_cover_set = vec![root.clone()];
         // let mut best_distance = best_candidate.point.distance(query); // the minimum distance from the last level (current_level + 1) 's cover set to the query point
 
@@ -173,7 +177,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
             for parent in current_cover_set.iter() {
                 next_cover_set.push(parent.clone());
                 for child in parent.non_self_descendants.borrow().iter() {
-                    let child_level = child.level.borrow().clone();
+                    let child_level = *child.level.borrow();
                     if child_level == i - 1 {
                         let distance = child.point.distance(query);
                         if distance < best_distance {
@@ -183,7 +187,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
 
                         next_cover_set.push(child.clone());
                     }
-                    if child_level <= i - 1 {
+                    if child_level < i {
                         has_remaining_children = true;
                     }
                 }
@@ -238,6 +242,71 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
         ))
     }
 
+    /// Returns up to `k` points closest to `query`, sorted by increasing distance.
+    /// A point equal to `query` is included if it is in the tree.
+    ///
+    /// The subtrees are expanded best first: every descendant of a node at level `l` is
+    /// within 2^(l + 1) of it, which bounds how close the subtree can get to the query, and
+    /// the search stops once no subtree can beat the k-th best distance.
+    pub fn k_nearest_neighbors(&self, query: &T, k: usize) -> Vec<(T, u32, f32)> {
+        let Some(root) = self.root.as_ref() else {
+            return Vec::new();
+        };
+        if k == 0 {
+            return Vec::new();
+        }
+        // a max-heap of the best k candidates so far, the worst of them on top
+        let mut best_candidates: BinaryHeap<(NotNan<f32>, RcKey<CoverTreeNode<T>>)> =
+            BinaryHeap::new();
+        let mut offer = |node: &Rc<CoverTreeNode<T>>, distance: f32| {
+            best_candidates.push((NotNan::new(distance).unwrap(), RcKey::new(node.clone())));
+            if best_candidates.len() > k {
+                best_candidates.pop();
+            }
+            if best_candidates.len() < k {
+                f32::INFINITY
+            } else {
+                best_candidates.peek().unwrap().0.into_inner()
+            }
+        };
+        let lower_bound = |node: &Rc<CoverTreeNode<T>>, distance: f32| {
+            Reverse(NotNan::new(distance - f32::exp2((*node.level.borrow() + 1) as f32)).unwrap())
+        };
+        let root_distance = root.point.distance(query);
+        let mut kth_distance = offer(root, root_distance);
+        // a min-heap of the subtrees still to expand, by how close they can get to the query
+        let mut subtrees = BinaryHeap::new();
+        subtrees.push((lower_bound(root, root_distance), RcKey::new(root.clone())));
+        while let Some((Reverse(bound), node)) = subtrees.pop() {
+            if bound.into_inner() >= kth_distance {
+                break;
+            }
+            for child in node.rc().non_self_descendants.borrow().iter() {
+                let distance = child.point.distance(query);
+                if distance < kth_distance {
+                    kth_distance = offer(child, distance);
+                }
+                let bound = lower_bound(child, distance);
+                if bound.0.into_inner() < kth_distance
+                    && !child.non_self_descendants.borrow().is_empty()
+                {
+                    subtrees.push((bound, RcKey::new(child.clone())));
+                }
+            }
+        }
+        best_candidates
+            .into_sorted_vec()
+            .into_iter()
+            .map(|(distance, node)| {
+                (
+                    node.rc().point.clone(),
+                    node.rc().index,
+                    distance.into_inner(),
+                )
+            })
+            .collect()
+    }
+
     pub fn remove(&mut self, target: &T) {
         let Some(root) = self.root.clone() else {
             panic!("Cover tree is empty, cannot remove target point.");
@@ -245,7 +314,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
         // first find the node to remove
         // assume there is only one node with the target point
         let mut level_to_cover_set: BTreeMap<i32, Vec<Weak<CoverTreeNode<T>>>> = BTreeMap::new();
-        let root_level = root.level.borrow().clone();
+        let root_level = *root.level.borrow();
         level_to_cover_set.insert(root_level, vec![Rc::downgrade(&root)]);
         let mut target_node_and_lowest_child: Option<(Rc<CoverTreeNode<T>>, i32)> = None;
         for i in (-root_level..).map(|x| -x) {
@@ -272,13 +341,11 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
                             children
                                 .iter()
                                 .map(|child| {
-                                    assert!(
-                                        child.level.borrow().clone() < node.level.borrow().clone()
-                                    );
-                                    child.level.borrow().clone()
+                                    assert!(*child.level.borrow() < *node.level.borrow());
+                                    *child.level.borrow()
                                 })
                                 .min()
-                                .unwrap_or_else(|| node.level.borrow().clone())
+                                .unwrap_or_else(|| *node.level.borrow())
                         };
                         target_node_and_lowest_child = Some((node, lowest_child));
                         break; // equivalent to early return in find_map
@@ -314,14 +381,14 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
                 // push all children of the node as potential candidates at the next level
                 let children = node.non_self_descendants.borrow();
                 for child in children.iter() {
-                    let child_level = child.level.borrow().clone();
+                    let child_level = *child.level.borrow();
                     if child_level == i - 1 {
                         let distance = child.point.distance(target);
                         if distance <= f32::exp2(i as f32) {
                             next_cover_set.push(Rc::downgrade(child));
                         }
                     }
-                    if child_level <= i - 1 {
+                    if child_level < i {
                         has_remaining_children = true;
                     }
                 }
@@ -359,7 +426,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
             self.root = None;
             let highest_target_child = target_children
                 .iter()
-                .max_by_key(|child| child.level.borrow().clone())
+                .max_by_key(|child| *child.level.borrow())
                 .cloned();
             let Some(highest_target_child) = highest_target_child else {
                 // the target node has no children, the tree is now empty
@@ -388,7 +455,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
         // until all children have been re-parented to a new parent level, or there are no more levels to search for parents
         for child in target_children.iter() {
             let mut valid_parent_and_parent_level: Option<(Rc<CoverTreeNode<T>>, i32)> = None;
-            let child_level = child.level.borrow().clone();
+            let child_level = *child.level.borrow();
             for new_parent_level in child_level + 1.. {
                 if let Some(potential_parents) = level_to_cover_set.get(&new_parent_level) {
                     // within the distance threshold, the children can be re-parented to the new parent level
@@ -434,7 +501,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
                 .expect("Failed to find a valid parent for a child of the removed node.");
             // place the child under the potential parent
             *child.ancestor.borrow_mut() = Rc::downgrade(&valid_parent);
-            let old_child_level = child.level.borrow().clone();
+            let old_child_level = *child.level.borrow();
             let new_child_level = valid_parent_level - 1;
             for child_covered_level in old_child_level + 1..=new_child_level {
                 let cover_set = level_to_cover_set.entry(child_covered_level).or_default();
@@ -443,7 +510,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
             *child.level.borrow_mut() = new_child_level;
             // assert level
             assert!(
-                valid_parent.level.borrow().clone() > child.level.borrow().clone(),
+                *valid_parent.level.borrow() > *child.level.borrow(),
                 "valid parent level: {}, child level: {}",
                 valid_parent.level.borrow().clone(),
                 child.level.borrow().clone()
@@ -463,7 +530,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
             return;
         };
         println!("Cover tree: -------------------------------");
-        let mut current_level = root.level.borrow().clone();
+        let mut current_level = *root.level.borrow();
         let mut current_level_node_sets = vec![vec![root.clone()]];
         let mut max_num_children = u32::MIN;
         loop {
@@ -495,11 +562,11 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
                     // add the node itself to the next level node set
                     next_level_node_set.push(node.clone());
                     for child in node.non_self_descendants.borrow().iter() {
-                        let child_level = child.level.borrow().clone();
+                        let child_level = *child.level.borrow();
                         if child_level == current_level - 1 {
                             next_level_node_set.push(child.clone());
                         }
-                        if child_level <= current_level - 1 {
+                        if child_level < current_level {
                             has_remaining_children = true;
                         }
                     }
@@ -523,7 +590,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
         let Some(root) = self.root.as_ref() else {
             return Ok(());
         };
-        let root_level = root.level.borrow().clone();
+        let root_level = *root.level.borrow();
         let mut current_level_nodes = vec![root.clone()];
         for current_level in (-root_level..).map(|x| -x) {
             let mut has_remaining_children = false;
@@ -539,7 +606,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
                 next_level_nodes.push(node.clone());
                 let children = node.non_self_descendants.borrow();
                 for child in children.iter() {
-                    let child_level = child.level.borrow().clone();
+                    let child_level = *child.level.borrow();
                     if child_level == current_level - 1 {
                         next_level_nodes.push(child.clone());
 
@@ -555,7 +622,7 @@ impl<T: Ord + Clone + Distance + std::fmt::Debug> CoverTree<T> {
                             ));
                         }
                     }
-                    if child_level <= current_level - 1 {
+                    if child_level < current_level {
                         has_remaining_children = true;
                     }
                 }
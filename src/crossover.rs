@@ -0,0 +1,360 @@
+use rand::{Rng, RngCore, seq::IndexedRandom};
+
+use crate::{
+    edge_assembly::edge_assembly_crossover, genetic::GeneticAlgorithm,
+    partition_crossover::partition_crossover, problem::Solution,
+};
+
+/// A recombination of two parent tours. The crossovers hand their children to a callback,
+/// which returns false once it wants no more of them.
+pub trait Crossover {
+    /// Builds children of `parent1` and `parent2` and calls `callback` with each of them,
+    /// until it returns false.
+    fn crossover(
+        &self,
+        parent1: &Solution,
+        parent2: &Solution,
+        neighbor_lists: &[Vec<u32>],
+        rng: &mut dyn RngCore,
+        callback: &mut dyn FnMut(&Solution) -> bool,
+    );
+
+    /// How the crossover is called in progress messages.
+    fn name(&self) -> &'static str;
+}
+
+/// Draws a crossover of a weighted `mix`, each with a probability proportional to its
+/// weight.
+pub fn choose_crossover<'a>(
+    mix: &'a [(Box<dyn Crossover>, f64)],
+    rng: &mut impl Rng,
+) -> &'a dyn Crossover {
+    let (crossover, _) = mix
+        .choose_weighted(rng, |&(_, weight)| weight)
+        .expect("crossover weights must be positive");
+    crossover.as_ref()
+}
+
+/// [`GeneticAlgorithm::random_crossover`] between two random positions.
+pub struct RandomCrossover;
+
+/// [`GeneticAlgorithm::precise_crossover`].
+pub struct PreciseCrossover;
+
+/// [`edge_assembly_crossover`].
+pub struct EdgeAssemblyCrossover;
+
+/// [`partition_crossover`].
+pub struct PartitionCrossover;
+
+/// Order crossover (OX): the child keeps a random slice of one parent in place and fills
+/// the other positions, from the end of the slice on, with the remaining cities in the
+/// order of the other parent. Builds one child from each parent.
+pub struct OrderCrossover;
+
+/// Partially mapped crossover (PMX): the child keeps a random slice of one parent in place
+/// and every other city where the other parent has it, unless the slice took that
+/// position, in which case the mapping between the slices of both parents moves it. Builds
+/// one child from each parent.
+pub struct PartiallyMappedCrossover;
+
+/// Cycle crossover (CX): the positions split into cycles, where the city one parent has
+/// at a position is at the next position of the cycle in the other parent. The child
+/// takes the cycles alternately from each parent, so every city keeps the position of
+/// one of them. Builds one child starting with each parent.
+pub struct CycleCrossover;
+
+/// Edge recombination crossover (ERX): the child starts at the first city of the first
+/// parent and moves on to the neighbor, in either parent, that has the fewest neighbors
+/// left, or to a random city when every neighbor is visited. Builds one child.
+pub struct EdgeRecombinationCrossover;
+
+/// Two different random positions in a tour of `n` cities, the smaller first.
+fn random_slice(n: usize, rng: &mut dyn RngCore) -> (usize, usize) {
+    loop {
+        let index1 = rng.random_range(0..n);
+        let index2 = rng.random_range(0..n);
+        if index1 != index2 {
+            return (index1.min(index2), index1.max(index2));
+        }
+    }
+}
+
+/// The index of every city in `order`.
+fn positions(order: &[u32]) -> Vec<usize> {
+    let mut positions = vec![0; order.len()];
+    for (i, &city) in order.iter().enumerate() {
+        positions[city as usize] = i;
+    }
+    positions
+}
+
+fn child_of(parent: &Solution, order: Vec<u32>) -> Solution {
+    Solution::new(order, parent.problem.clone(), None)
+}
+
+impl Crossover for RandomCrossover {
+    fn crossover(
+        &self,
+        parent1: &Solution,
+        parent2: &Solution,
+        _neighbor_lists: &[Vec<u32>],
+        rng: &mut dyn RngCore,
+        callback: &mut dyn FnMut(&Solution) -> bool,
+    ) {
+        let (start_index, end_index) = random_slice(parent1.order_without_loop.len(), rng);
+        callback(&GeneticAlgorithm::random_crossover(
+            parent1,
+            parent2,
+            start_index,
+            end_index,
+        ));
+    }
+
+    fn name(&self) -> &'static str {
+        "random"
+    }
+}
+
+impl Crossover for PreciseCrossover {
+    fn crossover(
+        &self,
+        parent1: &Solution,
+        parent2: &Solution,
+        _neighbor_lists: &[Vec<u32>],
+        _rng: &mut dyn RngCore,
+        callback: &mut dyn FnMut(&Solution) -> bool,
+    ) {
+        GeneticAlgorithm::precise_crossover(parent1, parent2, callback);
+    }
+
+    fn name(&self) -> &'static str {
+        "precise"
+    }
+}
+
+impl Crossover for EdgeAssemblyCrossover {
+    fn crossover(
+        &self,
+        parent1: &Solution,
+        parent2: &Solution,
+        neighbor_lists: &[Vec<u32>],
+        mut rng: &mut dyn RngCore,
+        callback: &mut dyn FnMut(&Solution) -> bool,
+    ) {
+        edge_assembly_crossover(parent1, parent2, neighbor_lists, &mut rng, callback);
+    }
+
+    fn name(&self) -> &'static str {
+        "edge assembly"
+    }
+}
+
+impl Crossover for PartitionCrossover {
+    fn crossover(
+        &self,
+        parent1: &Solution,
+        parent2: &Solution,
+        _neighbor_lists: &[Vec<u32>],
+        _rng: &mut dyn RngCore,
+        callback: &mut dyn FnMut(&Solution) -> bool,
+    ) {
+        partition_crossover(parent1, parent2, callback);
+    }
+
+    fn name(&self) -> &'static str {
+        "partition"
+    }
+}
+
+impl Crossover for OrderCrossover {
+    fn crossover(
+        &self,
+        parent1: &Solution,
+        parent2: &Solution,
+        _neighbor_lists: &[Vec<u32>],
+        rng: &mut dyn RngCore,
+        callback: &mut dyn FnMut(&Solution) -> bool,
+    ) {
+        let n = parent1.order_without_loop.len();
+        let (start, end) = random_slice(n, rng);
+        for (kept, other) in [(parent1, parent2), (parent2, parent1)] {
+            let mut child = kept.order_without_loop.clone();
+            let mut in_slice = vec![false; n];
+            for &city in &child[start..end] {
+                in_slice[city as usize] = true;
+            }
+            let mut position = end;
+            for i in 0..n {
+                let city = other.order_without_loop[(end + i) % n];
+                if !in_slice[city as usize] {
+                    child[position % n] = city;
+                    position += 1;
+                }
+            }
+            if !callback(&child_of(kept, child)) {
+                return;
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "order"
+    }
+}
+
+impl Crossover for PartiallyMappedCrossover {
+    fn crossover(
+        &self,
+        parent1: &Solution,
+        parent2: &Solution,
+        _neighbor_lists: &[Vec<u32>],
+        rng: &mut dyn RngCore,
+        callback: &mut dyn FnMut(&Solution) -> bool,
+    ) {
+        let n = parent1.order_without_loop.len();
+        let (start, end) = random_slice(n, rng);
+        for (kept, other) in [(parent1, parent2), (parent2, parent1)] {
+            let (kept_order, other_order) = (&kept.order_without_loop, &other.order_without_loop);
+            let other_positions = positions(other_order);
+            let mut child = other_order.clone();
+            child[start..end].copy_from_slice(&kept_order[start..end]);
+            let mut in_slice = vec![false; n];
+            for &city in &kept_order[start..end] {
+                in_slice[city as usize] = true;
+            }
+            // the cities the slice pushed out move to where the mapping leads out of it
+            for (i, &city) in other_order.iter().enumerate().take(end).skip(start) {
+                if in_slice[city as usize] {
+                    continue;
+                }
+                let mut position = i;
+                while (start..end).contains(&position) {
+                    position = other_positions[kept_order[position] as usize];
+                }
+                child[position] = city;
+            }
+            if !callback(&child_of(kept, child)) {
+                return;
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "partially mapped"
+    }
+}
+
+impl Crossover for CycleCrossover {
+    fn crossover(
+        &self,
+        parent1: &Solution,
+        parent2: &Solution,
+        _neighbor_lists: &[Vec<u32>],
+        _rng: &mut dyn RngCore,
+        callback: &mut dyn FnMut(&Solution) -> bool,
+    ) {
+        let n = parent1.order_without_loop.len();
+        let (order1, order2) = (&parent1.order_without_loop, &parent2.order_without_loop);
+        let positions1 = positions(order1);
+        // the index of the cycle of every position
+        let mut cycle_of = vec![usize::MAX; n];
+        let mut num_cycles = 0;
+        for first in 0..n {
+            if cycle_of[first] != usize::MAX {
+                continue;
+            }
+            let mut position = first;
+            while cycle_of[position] == usize::MAX {
+                cycle_of[position] = num_cycles;
+                position = positions1[order2[position] as usize];
+            }
+            num_cycles += 1;
+        }
+        // a single cycle gives the parents back
+        if num_cycles < 2 {
+            return;
+        }
+        for (kept, other) in [(parent1, parent2), (parent2, parent1)] {
+            let child = (0..n)
+                .map(|i| {
+                    if cycle_of[i] % 2 == 0 {
+                        kept.order_without_loop[i]
+                    } else {
+                        other.order_without_loop[i]
+                    }
+                })
+                .collect();
+            if !callback(&child_of(kept, child)) {
+                return;
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "cycle"
+    }
+}
+
+impl Crossover for EdgeRecombinationCrossover {
+    fn crossover(
+        &self,
+        parent1: &Solution,
+        parent2: &Solution,
+        _neighbor_lists: &[Vec<u32>],
+        rng: &mut dyn RngCore,
+        callback: &mut dyn FnMut(&Solution) -> bool,
+    ) {
+        let n = parent1.order_without_loop.len();
+        // the neighbors of every city in both parents, each once
+        let mut edge_map = vec![Vec::with_capacity(4); n];
+        for order in [&parent1.order_without_loop, &parent2.order_without_loop] {
+            for (i, &city) in order.iter().enumerate() {
+                for neighbor in [order[(i + n - 1) % n], order[(i + 1) % n]] {
+                    if neighbor != city && !edge_map[city as usize].contains(&neighbor) {
+                        edge_map[city as usize].push(neighbor);
+                    }
+                }
+            }
+        }
+        let mut unvisited = (0..n as u32).collect::<Vec<_>>();
+        let mut unvisited_index = (0..n).collect::<Vec<_>>();
+        let mut child = Vec::with_capacity(n);
+        let mut city = parent1.order_without_loop[0];
+        loop {
+            child.push(city);
+            let index = unvisited_index[city as usize];
+            unvisited.swap_remove(index);
+            if let Some(&moved) = unvisited.get(index) {
+                unvisited_index[moved as usize] = index;
+            }
+            let neighbors = std::mem::take(&mut edge_map[city as usize]);
+            for &neighbor in &neighbors {
+                edge_map[neighbor as usize].retain(|&other| other != city);
+            }
+            if unvisited.is_empty() {
+                break;
+            }
+            let fewest = neighbors
+                .iter()
+                .map(|&neighbor| edge_map[neighbor as usize].len())
+                .min();
+            city = match fewest {
+                Some(fewest) => {
+                    let candidates = neighbors
+                        .iter()
+                        .copied()
+                        .filter(|&neighbor| edge_map[neighbor as usize].len() == fewest)
+                        .collect::<Vec<_>>();
+                    *candidates.choose(rng).unwrap()
+                }
+                None => *unvisited.choose(rng).unwrap(),
+            };
+        }
+        callback(&child_of(parent1, child));
+    }
+
+    fn name(&self) -> &'static str {
+        "edge recombination"
+    }
+}
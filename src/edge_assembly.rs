@@ -0,0 +1,242 @@
+use rand::{Rng, RngCore, seq::SliceRandom};
+
+use crate::{
+    local_search::distance,
+    problem::{Problem, Solution},
+};
+
+/// How many children one crossover builds at most, each from a different AB-cycle.
+pub const EAX_MAX_CHILDREN: usize = 30;
+
+/// Marks an empty slot in an [`Adjacency`].
+const NONE: u32 = u32::MAX;
+
+/// The two neighbors of every city in a tour or in a set of subtours.
+type Adjacency = Vec<[u32; 2]>;
+
+fn adjacency(order: &[u32]) -> Adjacency {
+    let n = order.len();
+    let mut adjacency = vec![[NONE; 2]; n];
+    for (i, &city) in order.iter().enumerate() {
+        adjacency[city as usize] = [order[(i + n - 1) % n], order[(i + 1) % n]];
+    }
+    adjacency
+}
+
+fn replace(neighbors: &mut [u32; 2], old: u32, new: u32) {
+    let slot = neighbors.iter().position(|&city| city == old).unwrap();
+    neighbors[slot] = new;
+}
+
+/// Edge Assembly Crossover (EAX). The edges that only one of the parents has split into
+/// AB-cycles, which alternate between edges of the first parent and of the second. Each
+/// child is the first parent with the edges of one AB-cycle exchanged, the E-set, which
+/// leaves a set of subtours. They are merged, smallest first, by the cheapest exchange of
+/// two edges towards a candidate neighbor, as in a 2-opt move. Calls `callback` with
+/// every child until it returns false.
+pub fn edge_assembly_crossover(
+    parent1: &Solution,
+    parent2: &Solution,
+    neighbor_lists: &[Vec<u32>],
+    rng: &mut impl Rng,
+    callback: &mut dyn FnMut(&Solution) -> bool,
+) {
+    let problem = parent1.problem.upgrade().unwrap();
+    let parent1_adjacency = adjacency(&parent1.order_without_loop);
+    let parent2_adjacency = adjacency(&parent2.order_without_loop);
+    let mut cycles = ab_cycles(&parent1_adjacency, &parent2_adjacency, rng);
+    cycles.shuffle(rng);
+    for cycle in cycles.iter().take(EAX_MAX_CHILDREN) {
+        let mut adjacency = parent1_adjacency.clone();
+        apply_e_set(&mut adjacency, cycle);
+        merge_subtours(&mut adjacency, &problem, neighbor_lists);
+        let mut order = Vec::with_capacity(adjacency.len());
+        let (mut prev, mut city) = (adjacency[0][0], 0);
+        while order.len() < adjacency.len() {
+            order.push(city);
+            let next = if adjacency[city as usize][0] == prev {
+                adjacency[city as usize][1]
+            } else {
+                adjacency[city as usize][0]
+            };
+            (prev, city) = (city, next);
+        }
+        let child = Solution::new(order, parent1.problem.clone(), None);
+        if !callback(&child) {
+            return;
+        }
+    }
+}
+
+/// Splits the edges that only one parent has into AB-cycles. In a cycle `cities`, the edge
+/// from `cities[i]` to the next city belongs to the first parent for even `i` and to the
+/// second parent for odd `i`.
+fn ab_cycles(
+    parent1_adjacency: &Adjacency,
+    parent2_adjacency: &Adjacency,
+    rng: &mut impl Rng,
+) -> Vec<Vec<u32>> {
+    let n = parent1_adjacency.len();
+    // the edges of each parent that the other one lacks and no AB-cycle took yet; every
+    // city has as many left of the one parent as of the other
+    let mut remaining = [parent1_adjacency.clone(), parent2_adjacency.clone()];
+    for city in 0..n {
+        let [first, second] = &mut remaining;
+        for other in &mut first[city] {
+            if second[city].contains(other) {
+                replace(&mut second[city], *other, NONE);
+                *other = NONE;
+            }
+        }
+    }
+    let mut take = |parent: usize, city: u32, rng: &mut dyn RngCore| {
+        let neighbors = &mut remaining[parent][city as usize];
+        let slot = match *neighbors {
+            [NONE, NONE] => return None,
+            [_, NONE] => 0,
+            [NONE, _] => 1,
+            _ => rng.random_range(0..2),
+        };
+        let other = neighbors[slot];
+        neighbors[slot] = NONE;
+        replace(&mut remaining[parent][other as usize], city, NONE);
+        Some(other)
+    };
+    let mut starts = (0..n as u32).collect::<Vec<_>>();
+    starts.shuffle(rng);
+    let mut cycles = Vec::new();
+    let mut path = Vec::new();
+    // the index of every city on the path, by the parity of the index
+    let mut on_path = vec![[NONE; 2]; n];
+    for start in starts {
+        path.push(start);
+        on_path[start as usize][0] = 0;
+        loop {
+            let last = path.len() - 1;
+            let Some(city) = take(last % 2, path[last], rng) else {
+                // only the start is left on the path, and it has no edges left
+                on_path[start as usize][0] = NONE;
+                path.clear();
+                break;
+            };
+            let index = path.len();
+            let parity = index % 2;
+            let earlier = on_path[city as usize][parity];
+            if earlier == NONE {
+                on_path[city as usize][parity] = index as u32;
+                path.push(city);
+                continue;
+            }
+            // the path returned to a city where it continued with the same parent
+            let earlier = earlier as usize;
+            for (i, &city) in path.iter().enumerate().skip(earlier + 1) {
+                on_path[city as usize][i % 2] = NONE;
+            }
+            let mut cycle = path.split_off(earlier);
+            if earlier % 2 == 1 {
+                // start the cycle with an edge of the first parent
+                cycle.rotate_left(1);
+            }
+            cycles.push(cycle);
+            path.push(city);
+        }
+    }
+    cycles
+}
+
+/// Replaces the edges of the first parent in `cycle` with those of the second parent.
+fn apply_e_set(adjacency: &mut Adjacency, cycle: &[u32]) {
+    let len = cycle.len();
+    for i in (0..len).step_by(2) {
+        let (a, b) = (cycle[i], cycle[(i + 1) % len]);
+        replace(&mut adjacency[a as usize], b, NONE);
+        replace(&mut adjacency[b as usize], a, NONE);
+    }
+    for i in (1..len).step_by(2) {
+        let (a, b) = (cycle[i], cycle[(i + 1) % len]);
+        replace(&mut adjacency[a as usize], NONE, b);
+        replace(&mut adjacency[b as usize], NONE, a);
+    }
+}
+
+/// Merges the subtours of `adjacency` into one tour. The smallest subtour is merged first,
+/// by the cheapest exchange of one of its edges and an edge of another subtour where the
+/// new edges join a city to one of its candidates, or to any city when no candidate lies
+/// outside the subtour.
+fn merge_subtours(adjacency: &mut Adjacency, problem: &Problem, neighbor_lists: &[Vec<u32>]) {
+    let n = adjacency.len();
+    let mut subtour_of = vec![NONE; n];
+    let mut subtours: Vec<Vec<u32>> = Vec::new();
+    for first in 0..n as u32 {
+        if subtour_of[first as usize] != NONE {
+            continue;
+        }
+        let mut subtour = Vec::new();
+        let (mut prev, mut city) = (adjacency[first as usize][0], first);
+        loop {
+            subtour_of[city as usize] = subtours.len() as u32;
+            subtour.push(city);
+            let next = if adjacency[city as usize][0] == prev {
+                adjacency[city as usize][1]
+            } else {
+                adjacency[city as usize][0]
+            };
+            (prev, city) = (city, next);
+            if city == first {
+                break;
+            }
+        }
+        subtours.push(subtour);
+    }
+    let mut alive = (0..subtours.len()).collect::<Vec<_>>();
+    while alive.len() > 1 {
+        let (position, &smallest) = alive
+            .iter()
+            .enumerate()
+            .min_by_key(|&(_, &subtour)| subtours[subtour].len())
+            .unwrap();
+        alive.swap_remove(position);
+        let cities = std::mem::take(&mut subtours[smallest]);
+        let outside = |other: u32| subtour_of[other as usize] != smallest as u32;
+        let mut best: Option<(f64, [u32; 4])> = None;
+        let mut consider = |u: u32, w: u32| {
+            for &u_next in &adjacency[u as usize] {
+                for &w_next in &adjacency[w as usize] {
+                    let removed = distance(problem, u, u_next) + distance(problem, w, w_next);
+                    for (x, y) in [(w, w_next), (w_next, w)] {
+                        let delta =
+                            distance(problem, u, x) + distance(problem, u_next, y) - removed;
+                        if best.is_none_or(|(best_delta, _)| delta < best_delta) {
+                            best = Some((delta, [u, u_next, x, y]));
+                        }
+                    }
+                }
+            }
+        };
+        let has_candidate_outside = cities
+            .iter()
+            .any(|&u| neighbor_lists[u as usize].iter().any(|&w| outside(w)));
+        for &u in &cities {
+            if has_candidate_outside {
+                for &w in neighbor_lists[u as usize].iter().filter(|&&w| outside(w)) {
+                    consider(u, w);
+                }
+            } else {
+                for w in (0..n as u32).filter(|&w| outside(w)) {
+                    consider(u, w);
+                }
+            }
+        }
+        // removes (u, u_next) and (x, y), adds (u, x) and (u_next, y)
+        let (_, [u, u_next, x, y]) = best.unwrap();
+        replace(&mut adjacency[u as usize], u_next, x);
+        replace(&mut adjacency[u_next as usize], u, y);
+        replace(&mut adjacency[x as usize], y, u);
+        replace(&mut adjacency[y as usize], x, u_next);
+        let target = subtour_of[x as usize];
+        for &city in &cities {
+            subtour_of[city as usize] = target;
+        }
+        subtours[target as usize].extend(cities);
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    rc::Rc,
+};
+
+use crate::problem::{City, Problem, Solution};
+
+/// Errors produced while reading the homework input format.
+/// Line numbers are 1-based, matching what an editor shows.
+#[derive(Debug)]
+pub enum FormatError {
+    Io(io::Error),
+    /// Malformed JSON input; the error carries its own line and column.
+    Json(serde_json::Error),
+    /// The input ended before the city count line.
+    MissingCityCount,
+    InvalidCityCount {
+        line: usize,
+        content: String,
+    },
+    /// A city line does not have exactly three fields.
+    WrongFieldCount {
+        line: usize,
+        found: usize,
+    },
+    /// A coordinate is not a non-negative integer that fits in a `u32`.
+    InvalidCoordinate {
+        line: usize,
+        field: usize,
+        content: String,
+    },
+    /// The input ended before the announced number of cities was read.
+    MissingCities {
+        expected: usize,
+        found: usize,
+    },
+    /// Non-empty content after the last announced city.
+    TrailingContent {
+        line: usize,
+    },
+    /// A tour file is empty, so it has no total distance line.
+    MissingTotalDistance,
+    InvalidTotalDistance {
+        line: usize,
+        content: String,
+    },
+    /// A line of an index tour is not a non-negative integer.
+    InvalidIndex {
+        line: usize,
+        content: String,
+    },
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatError::Io(e) => write!(f, "I/O error: {}", e),
+            FormatError::Json(e) => write!(f, "invalid JSON: {}", e),
+            FormatError::MissingCityCount => write!(f, "input is empty, expected a city count"),
+            FormatError::InvalidCityCount { line, content } => {
+                write!(f, "line {}: invalid city count {:?}", line, content)
+            }
+            FormatError::WrongFieldCount { line, found } => {
+                write!(f, "line {}: expected 3 coordinates, found {}", line, found)
+            }
+            FormatError::InvalidCoordinate {
+                line,
+                field,
+                content,
+            } => write!(
+                f,
+                "line {}: coordinate {} is not a non-negative integer: {:?}",
+                line, field, content
+            ),
+            FormatError::MissingCities { expected, found } => write!(
+                f,
+                "expected {} cities but the input ended after {}",
+                expected, found
+            ),
+            FormatError::TrailingContent { line } => {
+                write!(f, "line {}: unexpected content after the last city", line)
+            }
+            FormatError::MissingTotalDistance => {
+                write!(f, "tour is empty, expected a total distance")
+            }
+            FormatError::InvalidTotalDistance { line, content } => {
+                write!(f, "line {}: invalid total distance {:?}", line, content)
+            }
+            FormatError::InvalidIndex { line, content } => {
+                write!(f, "line {}: invalid city index {:?}", line, content)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FormatError::Io(e) => Some(e),
+            FormatError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(e: io::Error) -> Self {
+        FormatError::Io(e)
+    }
+}
+
+/// Opens `path` for reading, with `-` meaning stdin.
+pub fn open_input(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    if path == Path::new("-") {
+        Ok(Box::new(BufReader::new(io::stdin())))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+/// Creates `path` for writing, with `-` meaning stdout.
+pub fn create_output(path: &Path) -> io::Result<Box<dyn Write>> {
+    if path == Path::new("-") {
+        Ok(Box::new(BufWriter::new(io::stdout())))
+    } else {
+        Ok(Box::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+/// Yields `(line_number, line)` pairs, skipping blank lines.
+fn non_empty_lines(
+    reader: impl BufRead,
+) -> impl Iterator<Item = Result<(usize, String), FormatError>> {
+    reader
+        .lines()
+        .enumerate()
+        .map(|(i, line)| line.map(|line| (i + 1, line)).map_err(FormatError::from))
+        .filter(|line| !matches!(line, Ok((_, content)) if content.trim().is_empty()))
+}
+
+fn parse_city(line_number: usize, line: &str) -> Result<City, FormatError> {
+    let fields = line.split_whitespace().collect::<Vec<_>>();
+    if fields.len() != 3 {
+        return Err(FormatError::WrongFieldCount {
+            line: line_number,
+            found: fields.len(),
+        });
+    }
+    let mut coords = [0u32; 3];
+    for (i, field) in fields.iter().enumerate() {
+        coords[i] = field
+            .parse::<u32>()
+            .map_err(|_| FormatError::InvalidCoordinate {
+                line: line_number,
+                field: i + 1,
+                content: field.to_string(),
+            })?;
+    }
+    Ok(City::new(coords[0], coords[1], coords[2]))
+}
+
+/// A tour as written by [`Solution::write_to`], before it is matched against a [`Problem`].
+pub struct TourFile {
+    pub total_distance: f32,
+    /// Every city line in file order, with its line number.
+    /// A closed tour repeats the first city at the end.
+    pub cities: Vec<(usize, City)>,
+}
+
+impl TourFile {
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, FormatError> {
+        let mut lines = non_empty_lines(reader);
+        let (line_number, first_line) = lines.next().ok_or(FormatError::MissingTotalDistance)??;
+        let total_distance: f32 =
+            first_line
+                .trim()
+                .parse()
+                .map_err(|_| FormatError::InvalidTotalDistance {
+                    line: line_number,
+                    content: first_line.trim().to_string(),
+                })?;
+        let mut cities = Vec::new();
+        for line in lines {
+            let (line_number, line) = line?;
+            cities.push((line_number, parse_city(line_number, &line)?));
+        }
+        Ok(TourFile {
+            total_distance,
+            cities,
+        })
+    }
+}
+
+impl Problem {
+    /// Reads the homework format: a city count followed by one `x y z` line per city.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, FormatError> {
+        let mut lines = non_empty_lines(reader);
+        let (line_number, first_line) = lines.next().ok_or(FormatError::MissingCityCount)??;
+        let num_cities: usize =
+            first_line
+                .trim()
+                .parse()
+                .map_err(|_| FormatError::InvalidCityCount {
+                    line: line_number,
+                    content: first_line.trim().to_string(),
+                })?;
+        // the count is untrusted, so a huge one must not allocate before the cities arrive
+        let mut cities = Vec::with_capacity(num_cities.min(1 << 16));
+        while cities.len() < num_cities {
+            let Some(line) = lines.next() else {
+                return Err(FormatError::MissingCities {
+                    expected: num_cities,
+                    found: cities.len(),
+                });
+            };
+            let (line_number, line) = line?;
+            cities.push(parse_city(line_number, &line)?);
+        }
+        if let Some(line) = lines.next() {
+            let (line_number, _) = line?;
+            return Err(FormatError::TrailingContent { line: line_number });
+        }
+        Ok(Problem::new(cities))
+    }
+
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "{}", self.cities.len())?;
+        for city in &self.cities {
+            writeln!(writer, "{} {} {}", city.x, city.y, city.z)?;
+        }
+        writer.flush()
+    }
+}
+
+impl Solution {
+    /// Writes the homework output format: the total distance, then every city's
+    /// coordinates in tour order with the first city repeated at the end.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        let problem: Rc<Problem> = self.problem.upgrade().expect("Problem has been dropped");
+        writeln!(writer, "{}", self.total_distance())?;
+        let closing_city = self.order_without_loop.first();
+        for &city_index in self.order_without_loop.iter().chain(closing_city) {
+            let city = &problem.cities[city_index as usize];
+            writeln!(writer, "{} {} {}", city.x, city.y, city.z)?;
+        }
+        writer.flush()
+    }
+}
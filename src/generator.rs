@@ -1,4 +1,3 @@
-use ordered_float::NotNan;
 use rand::distr::Uniform;
 use rand::prelude::*;
 use std::f64::consts::PI;
@@ -56,4 +55,33 @@ impl ProblemGenerator {
         cities.shuffle(&mut *rng);
         Problem { cities }
     }
+    /// Cities spread over `num_clusters` balls of radius `cluster_radius` whose centers are
+    /// uniform in the sphere, with every city in a uniformly chosen cluster.
+    pub fn generate_clustered_problem(&self, num_clusters: usize, cluster_radius: f32) -> Problem {
+        assert!(num_clusters > 0);
+        assert!(cluster_radius <= self.max_radius);
+        let mut rng = self.rng.borrow_mut();
+        let dist = Uniform::new(0.0, 1.0).unwrap();
+        let offset = cluster_radius as u32;
+        let centers = (0..num_clusters)
+            .map(|_| {
+                let center =
+                    generate_point_in_sphere(self.max_radius - cluster_radius, &dist, &mut *rng);
+                City::new(center.x + offset, center.y + offset, center.z + offset)
+            })
+            .collect::<Vec<_>>();
+        let mut cities: BTreeSet<City> = BTreeSet::new();
+        while cities.len() < self.num_cities {
+            let center = centers[rng.random_range(0..num_clusters)];
+            let point = generate_point_in_sphere(cluster_radius, &dist, &mut *rng);
+            cities.insert(City::new(
+                center.x + point.x - offset,
+                center.y + point.y - offset,
+                center.z + point.z - offset,
+            ));
+        }
+        let mut cities = cities.into_iter().collect::<Vec<_>>();
+        cities.shuffle(&mut *rng);
+        Problem { cities }
+    }
 }
@@ -1,43 +1,110 @@
 use core::f32;
 use std::{
     cell::RefCell,
-    cmp::{Ordering, Reverse},
-    collections::{BTreeSet, BinaryHeap, VecDeque},
+    collections::{BTreeSet, VecDeque},
     rc::Rc,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use ordered_float::NotNan;
-use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
-use crate::problem::{Population, Problem, RcKey, Solution};
+use crate::{
+    construction::Construction,
+    crossover::{
+        Crossover, EdgeAssemblyCrossover, PartitionCrossover, PreciseCrossover, RandomCrossover,
+        choose_crossover,
+    },
+    local_search::{LOCAL_SEARCH_NEIGHBORS, LocalSearch},
+    mutation::{Mutation, mutate_with_rates},
+    neighbors::nearest_neighbor_lists,
+    problem::{Population, Problem, Solution},
+    replacement::{ElitistReplacement, Offspring, ReplacementStrategy},
+    selection::{RouletteSelection, SelectionStrategy},
+};
 
 pub struct GeneticAlgorithm {
     pub problem: Rc<Problem>,
     pub population_size: usize,
     pub extra_population_size: usize,
+    /// Tours added to the initial population next to the constructed tours,
+    /// e.g. the result of a previous run.
+    pub initial_solutions: Vec<Solution>,
+    /// Heuristics that build the initial population, each run from every start city.
+    pub constructions: Vec<Construction>,
+    /// Local searches that polish every tour of the initial population.
+    pub local_searches: Vec<LocalSearch>,
+    /// Local searches that improve the children of the crossovers before they are
+    /// evaluated and deduplicated, which makes the algorithm memetic. Empty keeps the
+    /// children as the crossovers build them.
+    pub offspring_local_searches: Vec<LocalSearch>,
+    /// The share of children that the offspring local searches improve.
+    pub offspring_local_search_probability: f64,
+    /// The crossovers that build the children, each drawn with a probability
+    /// proportional to its weight.
+    pub crossovers: Vec<(Box<dyn Crossover>, f64)>,
+    /// The mutations of the children, each applied to a child with its rate, before the
+    /// offspring local searches. Empty leaves the children as the crossovers build them.
+    pub mutations: Vec<(Mutation, f64)>,
+    /// How the parents of the crossovers are picked from the population.
+    pub selection: Rc<dyn SelectionStrategy>,
+    /// How the survivors of every generation are picked from the population and the
+    /// children.
+    pub replacement: Box<dyn ReplacementStrategy>,
     rng: RefCell<StdRng>,
 }
 
+/// The index the crossover is currently looking for.
 enum CrossoverState {
-    FindingStartIndex,
-    FindingDifferentAfterStart {
+    StartIndex,
+    DifferentAfterStart {
         start_index: u32,
     },
-    FindingEndIndex {
+    EndIndex {
         start_index: u32,
         parent1_set: BTreeSet<u32>,
         parent2_set: BTreeSet<u32>,
     },
 }
 
+pub const DEFAULT_SEED: u64 = 42;
+/// How many crossovers, each drawn by weight, a trial of a generation runs.
+const CROSSOVERS_PER_TRIAL: usize = 7;
+
 impl GeneticAlgorithm {
     pub fn new(problem: Rc<Problem>, population_size: usize, extra_population_size: usize) -> Self {
-        let rng = RefCell::new(StdRng::seed_from_u64(42));
+        Self::with_seed(
+            problem,
+            population_size,
+            extra_population_size,
+            DEFAULT_SEED,
+        )
+    }
+    pub fn with_seed(
+        problem: Rc<Problem>,
+        population_size: usize,
+        extra_population_size: usize,
+        seed: u64,
+    ) -> Self {
+        let rng = RefCell::new(StdRng::seed_from_u64(seed));
         Self {
             problem,
             population_size,
             extra_population_size,
+            initial_solutions: Vec::new(),
+            constructions: vec![Construction::NearestNeighbor],
+            local_searches: Vec::new(),
+            offspring_local_searches: Vec::new(),
+            offspring_local_search_probability: 1.0,
+            crossovers: vec![
+                (Box::new(PreciseCrossover), 4.0),
+                (Box::new(EdgeAssemblyCrossover), 1.0),
+                (Box::new(PartitionCrossover), 1.0),
+                (Box::new(RandomCrossover), 1.0),
+            ],
+            mutations: Vec::new(),
+            selection: Rc::new(RouletteSelection),
+            replacement: Box::new(ElitistReplacement { elite_count: None }),
             rng,
         }
     }
@@ -53,9 +120,12 @@ impl GeneticAlgorithm {
             .cloned()
             .collect();
         let mut child_order = parent2.order_without_loop.clone();
-        for i in start_index..end_index {
-            parent2_replaced_elements.remove(&parent1.order_without_loop[i]);
-            child_order[i] = parent1.order_without_loop[i];
+        for (child_element, &parent1_element) in child_order[start_index..end_index]
+            .iter_mut()
+            .zip(&parent1.order_without_loop[start_index..end_index])
+        {
+            parent2_replaced_elements.remove(&parent1_element);
+            *child_element = parent1_element;
         }
         let mut visited = vec![false; parent1.order_without_loop.len()];
         let mut dirty_indices = Vec::new();
@@ -67,10 +137,7 @@ impl GeneticAlgorithm {
             }
         }
         assert!(parent2_replaced_elements.len() == dirty_indices.len());
-        for (dirty_index, replacement) in dirty_indices
-            .into_iter()
-            .zip(parent2_replaced_elements.into_iter())
-        {
+        for (dirty_index, replacement) in dirty_indices.into_iter().zip(parent2_replaced_elements) {
             child_order[dirty_index] = replacement;
         }
         let child = Solution::new(
@@ -95,22 +162,22 @@ impl GeneticAlgorithm {
             for parent2_order in [&mut parent2_order_forward, &mut parent2_order_reversed] {
                 let mut next_start_to_explore: Option<u32> = Some(0);
                 while let Some(current_start) = next_start_to_explore.take() {
-                    let mut crossover_state = CrossoverState::FindingStartIndex;
+                    let mut crossover_state = CrossoverState::StartIndex;
                     for i in current_start as usize..parent1_order.len() {
                         let parent1_element = parent1_order[i];
                         let parent2_element = parent2_order[i];
                         match crossover_state {
-                            CrossoverState::FindingStartIndex => {
+                            CrossoverState::StartIndex => {
                                 if parent1_element == parent2_element {
-                                    crossover_state = CrossoverState::FindingDifferentAfterStart {
+                                    crossover_state = CrossoverState::DifferentAfterStart {
                                         start_index: i as u32,
                                     };
                                 }
                             }
-                            CrossoverState::FindingDifferentAfterStart { start_index } => {
+                            CrossoverState::DifferentAfterStart { start_index } => {
                                 if parent1_element == parent2_element {
                                     assert!(i as u32 == start_index + 1);
-                                    crossover_state = CrossoverState::FindingDifferentAfterStart {
+                                    crossover_state = CrossoverState::DifferentAfterStart {
                                         start_index: i as u32,
                                     };
                                 } else {
@@ -118,14 +185,14 @@ impl GeneticAlgorithm {
                                     let mut parent2_set: BTreeSet<u32> = BTreeSet::new();
                                     parent1_set.insert(parent1_element);
                                     parent2_set.insert(parent2_element);
-                                    crossover_state = CrossoverState::FindingEndIndex {
+                                    crossover_state = CrossoverState::EndIndex {
                                         start_index,
                                         parent1_set,
                                         parent2_set,
                                     };
                                 }
                             }
-                            CrossoverState::FindingEndIndex {
+                            CrossoverState::EndIndex {
                                 start_index,
                                 mut parent1_set,
                                 mut parent2_set,
@@ -141,7 +208,7 @@ impl GeneticAlgorithm {
                                         let end_index = i;
                                         let mut new_child1_order = parent1_order.clone();
                                         let parent2_order_slice = (start_index as usize..end_index)
-                                            .map(|x| *parent2_order.get(x as usize).unwrap())
+                                            .map(|x| *parent2_order.get(x).unwrap())
                                             .collect::<Vec<u32>>();
                                         new_child1_order[start_index as usize..end_index]
                                             .copy_from_slice(parent2_order_slice.as_slice());
@@ -169,25 +236,19 @@ impl GeneticAlgorithm {
                                             Rc::downgrade(&parent2.problem.upgrade().unwrap()),
                                             None,
                                         );
-                                        if !callback(&new_child2) {
-                                            return;
-                                        }
-                                        return;
                                         assert!(
                                             new_child2
                                                 .is_valid(parent2.order_without_loop.len() as u32)
                                         );
-                                        crossover_state =
-                                            CrossoverState::FindingDifferentAfterStart {
-                                                start_index: i as u32,
-                                            };
+                                        callback(&new_child2);
+                                        return;
                                     } else {
                                         if next_start_to_explore.is_none() {
                                             next_start_to_explore = Some(i as u32);
                                         }
                                         parent1_set.insert(parent1_element);
                                         parent2_set.insert(parent2_element);
-                                        crossover_state = CrossoverState::FindingEndIndex {
+                                        crossover_state = CrossoverState::EndIndex {
                                             start_index,
                                             parent1_set,
                                             parent2_set,
@@ -196,7 +257,7 @@ impl GeneticAlgorithm {
                                 } else {
                                     parent1_set.insert(parent1_element);
                                     parent2_set.insert(parent2_element);
-                                    crossover_state = CrossoverState::FindingEndIndex {
+                                    crossover_state = CrossoverState::EndIndex {
                                         start_index,
                                         parent1_set,
                                         parent2_set,
@@ -222,36 +283,107 @@ impl GeneticAlgorithm {
 
         let mut visited_total_lengths: BTreeSet<NotNan<f32>> = BTreeSet::new();
 
-        // the initial population are all generated from nearest neighbor with different starting points
-        for start_index in 0..num_cities {
-            if timer.elapsed().as_secs() >= time_limit_secs {
-                return current_best_solution
-                    .as_ref()
-                    .expect("No solution found")
-                    .as_ref()
-                    .clone();
+        let deadline = *timer + Duration::from_secs(time_limit_secs);
+        // the local searches and the edge assembly crossover both work on candidate lists
+        let neighbor_lists = nearest_neighbor_lists(&self.problem, LOCAL_SEARCH_NEIGHBORS);
+        let polish = |solution: Solution| {
+            if self.local_searches.is_empty() {
+                solution
+            } else {
+                solution.improve_with(&self.local_searches, &neighbor_lists, Some(deadline))
+            }
+        };
+        let mutate_child = |child: Solution, rng: &mut StdRng| match mutate_with_rates(
+            &self.mutations,
+            &child.order_without_loop,
+            rng,
+        ) {
+            Some(order) => Solution::new(order, child.problem.clone(), None),
+            None => child,
+        };
+        let improve_child = |child: Solution, rng: &mut StdRng| {
+            let child = mutate_child(child, rng);
+            if self.offspring_local_searches.is_empty()
+                || rng.random::<f64>() >= self.offspring_local_search_probability
+            {
+                child
+            } else {
+                child.improve_with(
+                    &self.offspring_local_searches,
+                    &neighbor_lists,
+                    Some(deadline),
+                )
             }
-            let solution = Solution::from_nearest_neighbor(&self.problem, start_index);
-            let solution = Rc::new(solution);
+        };
+
+        for solution in &self.initial_solutions {
+            assert!(solution.is_valid(num_cities as u32));
+            let solution = Rc::new(polish(solution.clone()));
             let total_distance = solution.total_distance();
             if total_distance < current_best_distance {
                 current_best_distance = total_distance;
                 current_best_solution = Some(solution.clone());
-                println!(
-                    "New best solution found by nearest neighbor with start index {}: {}",
-                    start_index, total_distance
-                );
+                eprintln!("Initial solution: {}", total_distance);
+            }
+            if visited_total_lengths.insert(NotNan::new(total_distance).unwrap()) {
+                population.push(solution);
+            }
+        }
+
+        // the initial population is generated by every construction with different starting points
+        // constructions that ignore the start city build one tour each, too few to recombine, so
+        // nearest neighbor tours from every start city fill up the population then
+        let fill = (!self
+            .constructions
+            .iter()
+            .any(Construction::depends_on_start_city))
+        .then_some(Construction::NearestNeighbor);
+        for start_index in 0..num_cities {
+            for construction in self.constructions.iter().chain(&fill) {
+                if start_index > 0 && !construction.depends_on_start_city() {
+                    continue;
+                }
+                if timer.elapsed().as_secs() >= time_limit_secs {
+                    return current_best_solution
+                        .as_ref()
+                        .expect("No solution found")
+                        .as_ref()
+                        .clone();
+                }
+                let solution = polish(construction.build(&self.problem, start_index));
+                let solution = Rc::new(solution);
+                let total_distance = solution.total_distance();
+                if total_distance < current_best_distance {
+                    current_best_distance = total_distance;
+                    current_best_solution = Some(solution.clone());
+                    eprintln!(
+                        "New best solution found by {:?} with start index {}: {}",
+                        construction, start_index, total_distance
+                    );
+                }
+                visited_total_lengths.insert(NotNan::new(total_distance).unwrap());
+                population.push(solution);
             }
-            visited_total_lengths.insert(NotNan::new(total_distance).unwrap());
-            population.push(solution);
         }
         // let population: Vec<Rc<Solution>> =
         //     population.drain().map(|rev| rev.0.1.rc().clone()).collect();
-        let mut population = Population::new(population);
-        assert!(population.solutions.len() > 1);
+        // a single city gives a single tour, with nothing to recombine
+        if population.len() < 2 {
+            return current_best_solution
+                .as_ref()
+                .expect("No solution found")
+                .as_ref()
+                .clone();
+        }
+        let mut population = Population::with_selection(population, self.selection.clone());
+        eprintln!(
+            "Selection: {}, replacement: {}",
+            self.selection.name(),
+            self.replacement.name()
+        );
         let mut generation: u32 = 0;
         loop {
-            println!("Generation {}", generation);
+            eprintln!("Generation {}", generation);
             generation += 1;
             if timer.elapsed().as_secs() >= time_limit_secs {
                 return current_best_solution
@@ -267,16 +399,15 @@ impl GeneticAlgorithm {
                 if population.solutions.len() + new_solutions.len()
                     >= self.population_size + self.extra_population_size
                 {
-                    println!(
+                    eprintln!(
                         "Population full, stopping all crossover -----------------------------------"
                     );
                     break;
                 }
 
-                const PRECISE_CROSSOVERS_PER_TRIAL: usize = 4;
-                for _ in 0..PRECISE_CROSSOVERS_PER_TRIAL {
+                for _ in 0..CROSSOVERS_PER_TRIAL {
                     if timer.elapsed().as_secs() >= time_limit_secs {
-                        println!(
+                        eprintln!(
                             "Time limit reached, stopping all crossover -----------------------------------"
                         );
                         break;
@@ -284,7 +415,7 @@ impl GeneticAlgorithm {
                     if population.solutions.len() + new_solutions.len()
                         >= self.population_size + self.extra_population_size
                     {
-                        println!(
+                        eprintln!(
                             "Population full, stopping all crossover -----------------------------------"
                         );
                         break;
@@ -297,18 +428,23 @@ impl GeneticAlgorithm {
                         }
                     };
                     assert!(parent1.is_valid(num_cities as u32));
+                    let crossover = choose_crossover(&self.crossovers, &mut *rng);
+                    // the callback needs the generator, so the crossover gets its own
+                    let mut crossover_rng = StdRng::seed_from_u64(rng.random());
                     // Self::crossover(parent1, parent2, start_index, end_index)
                     let mut callback = |child: &Solution| -> bool {
                         assert!(
                             population.solutions.len() + new_solutions.len()
                                 < self.population_size + self.extra_population_size
                         );
+                        let child = improve_child(child.clone(), &mut rng);
                         let child_distance = child.total_distance();
                         if child_distance < current_best_distance {
                             current_best_distance = child_distance;
                             current_best_solution = Some(Rc::new(child.clone()));
-                            println!(
-                                "New best solution found by precise crossover: {}",
+                            eprintln!(
+                                "New best solution found by {} crossover: {}",
+                                crossover.name(),
                                 child_distance
                             );
                         }
@@ -318,7 +454,10 @@ impl GeneticAlgorithm {
                             //     child_distance,
                             //     new_solutions.len()
                             // );
-                            new_solutions.push(Rc::new(child.clone()));
+                            new_solutions.push(Offspring {
+                                solution: Rc::new(child),
+                                parents: [parent1.clone(), parent2.clone()],
+                            });
                         }
                         let population_not_full = population.solutions.len() + new_solutions.len()
                             < self.population_size + self.extra_population_size;
@@ -326,54 +465,16 @@ impl GeneticAlgorithm {
                         // returns true if we want to continue generating more children
                         population_not_full && has_time
                     };
-                    Self::precise_crossover(&parent1, &parent2, &mut callback);
-                }
-                const RANDOM_CROSSOVER_PER_TRIAL: usize = 1;
-                for _ in 0..RANDOM_CROSSOVER_PER_TRIAL {
-                    if timer.elapsed().as_secs() >= time_limit_secs {
-                        println!(
-                            "Time limit reached, stopping all crossover -----------------------------------"
-                        );
-                        break;
-                    }
-                    let (parent1, parent2) = loop {
-                        let parent1 = population.sample_parent(&mut rng);
-                        let parent2 = population.sample_parent(&mut rng);
-                        if !Rc::ptr_eq(&parent1, &parent2) {
-                            break (parent1, parent2);
-                        }
-                    };
-                    assert!(parent1.is_valid(num_cities as u32));
-                    let (start_index, end_index) = loop {
-                        let index1 = rng.random_range(0..num_cities);
-                        let index2 = rng.random_range(0..num_cities);
-                        if index1 < index2 {
-                            break (index1, index2);
-                        } else if index1 > index2 {
-                            break (index2, index1);
-                        }
-                    };
-                    let child = Self::random_crossover(&parent1, &parent2, start_index, end_index);
-                    let child_distance = child.total_distance();
-                    if child_distance < current_best_distance {
-                        current_best_distance = child_distance;
-                        current_best_solution = Some(Rc::new(child.clone()));
-                        println!(
-                            "New best solution found by random crossover: {}",
-                            child_distance
-                        );
-                    }
-                    if visited_total_lengths.insert(NotNan::new(child_distance).unwrap()) {
-                        // println!(
-                        //     "new random crossover distance: {}, new solution size: {}",
-                        //     child_distance,
-                        //     new_solutions.len()
-                        // );
-                        new_solutions.push(Rc::new(child));
-                    }
+                    crossover.crossover(
+                        &parent1,
+                        &parent2,
+                        &neighbor_lists,
+                        &mut crossover_rng,
+                        &mut callback,
+                    );
                 }
                 if timer.elapsed().as_secs() >= time_limit_secs {
-                    println!(
+                    eprintln!(
                         "Time limit reached, exiting from crossover -----------------------------------"
                     );
                     break;
@@ -386,13 +487,15 @@ impl GeneticAlgorithm {
                     .as_ref()
                     .clone();
             }
-            let mut combined_solutions = population.solutions;
-            combined_solutions.extend(new_solutions);
-            let mut combined_population = Population::new(combined_solutions);
             // let mut new_solutions: BTreeSet<RcKey<Solution>> = BTreeSet::new();
-            let new_solutions =
-                combined_population.shrink_population(self.population_size, &mut rng);
-            population = Population::new(new_solutions);
+            let new_solutions = population.shrink_population(
+                new_solutions,
+                self.population_size,
+                self.replacement.as_ref(),
+                Some(deadline),
+                &mut rng,
+            );
+            population = Population::with_selection(new_solutions, self.selection.clone());
             // while new_solutions.len() < self.population_size {
             //     if num_trials > self.population_size * 3 / 2 {
             //         println!(
@@ -0,0 +1,157 @@
+use std::rc::Rc;
+
+use ordered_float::NotNan;
+
+use crate::{
+    neighbors::{Grid, UnvisitedCities, edge_length, nearest_neighbor_lists},
+    problem::{Problem, Solution},
+};
+
+/// How many nearest neighbors of every city are considered as candidate edges.
+pub const GREEDY_EDGE_NEIGHBORS: usize = 10;
+
+pub(crate) const NO_CITY: u32 = u32::MAX;
+
+/// Union-find over cities, tracking which path fragment each city belongs to.
+pub(crate) struct Fragments {
+    parent: Vec<u32>,
+}
+
+impl Fragments {
+    pub(crate) fn new(num_cities: usize) -> Self {
+        Self {
+            parent: (0..num_cities as u32).collect(),
+        }
+    }
+
+    pub(crate) fn find(&mut self, city: u32) -> u32 {
+        let mut root = city;
+        while self.parent[root as usize] != root {
+            root = self.parent[root as usize];
+        }
+        // path compression
+        let mut current = city;
+        while self.parent[current as usize] != root {
+            let next = self.parent[current as usize];
+            self.parent[current as usize] = root;
+            current = next;
+        }
+        root
+    }
+
+    pub(crate) fn union(&mut self, a: u32, b: u32) {
+        let root_a = self.find(a);
+        let root_b = self.find(b);
+        self.parent[root_a as usize] = root_b;
+    }
+}
+
+/// Walks the path fragments given by the tour neighbors in `adjacent` (`NO_CITY` where
+/// missing), starting at the free end `first_end` and jumping from the end of each
+/// fragment to the nearest free end of another one. The free ends are kept in a grid and
+/// dropped from it as their fragments are walked, so every jump only scans the cells
+/// around its answer.
+pub(crate) fn join_fragments(problem: &Problem, adjacent: &[[u32; 2]], first_end: u32) -> Vec<u32> {
+    let num_cities = adjacent.len();
+    let ends = (0..num_cities as u32)
+        .filter(|&city| adjacent[city as usize].contains(&NO_CITY))
+        .collect::<Vec<_>>();
+    let end_cities = ends
+        .iter()
+        .map(|&city| problem.cities[city as usize])
+        .collect::<Vec<_>>();
+    // the index of every free end among `ends`
+    let mut end_index = vec![NO_CITY; num_cities];
+    for (index, &city) in ends.iter().enumerate() {
+        end_index[city as usize] = index as u32;
+    }
+    let grid = Grid::new(&end_cities);
+    let mut free_ends = UnvisitedCities::new(&grid, &end_cities);
+    let mut order = Vec::with_capacity(num_cities);
+    let mut current_end = Some(first_end);
+    while let Some(fragment_start) = current_end {
+        let mut previous = NO_CITY;
+        let mut current = fragment_start;
+        loop {
+            order.push(current);
+            let next = adjacent[current as usize]
+                .into_iter()
+                .find(|&neighbor| neighbor != NO_CITY && neighbor != previous);
+            match next {
+                Some(next) => {
+                    previous = current;
+                    current = next;
+                }
+                None => break,
+            }
+        }
+        // both ends of the fragment are taken now
+        free_ends.visit(end_index[fragment_start as usize]);
+        free_ends.visit(end_index[current as usize]);
+        current_end = free_ends
+            .nearest(end_index[current as usize])
+            .map(|index| ends[index as usize]);
+    }
+    order
+}
+
+impl Solution {
+    /// Builds the tour from the shortest edges first. An edge is rejected if one of its
+    /// cities already has two tour edges or if it would close a cycle before every city is
+    /// on it. Candidates come from the `GREEDY_EDGE_NEIGHBORS` nearest neighbors of every
+    /// city; the path fragments left over when they run out are joined nearest end first.
+    /// The tour is rotated to start at `start_index`.
+    pub fn from_greedy_edge(problem: &Rc<Problem>, start_index: usize) -> Self {
+        let num_cities = problem.cities.len();
+        assert!(start_index < num_cities);
+        let neighbor_lists = nearest_neighbor_lists(problem, GREEDY_EDGE_NEIGHBORS);
+        let mut candidate_edges = Vec::new();
+        for (a, neighbors) in neighbor_lists.iter().enumerate() {
+            for &b in neighbors {
+                let a = a as u32;
+                // the same edge shows up in both lists when the cities are mutual neighbors
+                if a < b || !neighbor_lists[b as usize].contains(&a) {
+                    let length = NotNan::new(edge_length(problem, a, b)).unwrap();
+                    candidate_edges.push((length, a.min(b), a.max(b)));
+                }
+            }
+        }
+        candidate_edges.sort_unstable();
+
+        // the two tour neighbors of every city, NO_CITY while missing
+        let mut adjacent = vec![[NO_CITY; 2]; num_cities];
+        let mut degree = vec![0u8; num_cities];
+        let mut fragments = Fragments::new(num_cities);
+        let mut num_edges = 0;
+        for (_, a, b) in candidate_edges {
+            if num_edges + 1 == num_cities {
+                break;
+            }
+            if degree[a as usize] == 2 || degree[b as usize] == 2 {
+                continue;
+            }
+            if fragments.find(a) == fragments.find(b) {
+                continue;
+            }
+            fragments.union(a, b);
+            adjacent[a as usize][degree[a as usize] as usize] = b;
+            adjacent[b as usize][degree[b as usize] as usize] = a;
+            degree[a as usize] += 1;
+            degree[b as usize] += 1;
+            num_edges += 1;
+        }
+
+        let first_end = (0..num_cities as u32)
+            .find(|&city| degree[city as usize] < 2)
+            .unwrap();
+        let mut order = join_fragments(problem, &adjacent, first_end);
+        assert_eq!(order.len(), num_cities);
+
+        let start_position = order
+            .iter()
+            .position(|&city| city == start_index as u32)
+            .unwrap();
+        order.rotate_left(start_position);
+        Solution::new(order, Rc::downgrade(problem), None)
+    }
+}
@@ -0,0 +1,166 @@
+use std::rc::Rc;
+
+use crate::{
+    cover_tree::Distance,
+    problem::{City, Problem, Solution},
+};
+
+/// A partial tour stored as a successor list, so that inserting a city is O(1).
+/// Every tour edge is identified by the city it starts from.
+struct InsertionTour<'a> {
+    cities: &'a [City],
+    next: Vec<u32>,
+    in_tour: Vec<bool>,
+    members: Vec<u32>,
+}
+
+impl<'a> InsertionTour<'a> {
+    fn new(cities: &'a [City], start_index: usize) -> Self {
+        let mut next = vec![u32::MAX; cities.len()];
+        next[start_index] = start_index as u32;
+        let mut in_tour = vec![false; cities.len()];
+        in_tour[start_index] = true;
+        Self {
+            cities,
+            next,
+            in_tour,
+            members: vec![start_index as u32],
+        }
+    }
+
+    fn distance(&self, a: u32, b: u32) -> f32 {
+        self.cities[a as usize].distance(&self.cities[b as usize])
+    }
+
+    /// The extra length of inserting `city` into the edge starting at `edge_start`.
+    fn insertion_cost(&self, edge_start: u32, city: u32) -> f32 {
+        let edge_end = self.next[edge_start as usize];
+        self.distance(edge_start, city) + self.distance(city, edge_end)
+            - self.distance(edge_start, edge_end)
+    }
+
+    /// The edge where inserting `city` is cheapest, and the cost of doing so.
+    fn cheapest_edge(&self, city: u32) -> (u32, f32) {
+        let mut best = (self.members[0], f32::INFINITY);
+        for &edge_start in &self.members {
+            let cost = self.insertion_cost(edge_start, city);
+            if cost < best.1 {
+                best = (edge_start, cost);
+            }
+        }
+        best
+    }
+
+    fn insert(&mut self, edge_start: u32, city: u32) {
+        self.next[city as usize] = self.next[edge_start as usize];
+        self.next[edge_start as usize] = city;
+        self.in_tour[city as usize] = true;
+        self.members.push(city);
+    }
+
+    fn into_solution(self, problem: &Rc<Problem>, start_index: usize) -> Solution {
+        let mut order = Vec::with_capacity(self.members.len());
+        let mut current = start_index as u32;
+        loop {
+            order.push(current);
+            current = self.next[current as usize];
+            if current == start_index as u32 {
+                break;
+            }
+        }
+        Solution::new(order, Rc::downgrade(problem), None)
+    }
+}
+
+/// Shared loop of nearest and farthest insertion: repeatedly pick the city whose
+/// distance to the tour is smallest (`nearest == true`) or largest, and insert it
+/// where it adds the least length. O(n²) time and O(n) memory.
+fn distance_driven_insertion(problem: &Rc<Problem>, start_index: usize, nearest: bool) -> Solution {
+    assert!(start_index < problem.cities.len());
+    let cities = &problem.cities;
+    let mut tour = InsertionTour::new(cities, start_index);
+    let mut distance_to_tour = cities
+        .iter()
+        .map(|city| city.distance(&cities[start_index]))
+        .collect::<Vec<_>>();
+    for _ in 1..cities.len() {
+        let mut selected: Option<(u32, f32)> = None;
+        for (i, &distance) in distance_to_tour.iter().enumerate() {
+            if tour.in_tour[i] {
+                continue;
+            }
+            let is_better = match selected {
+                None => true,
+                Some((_, best)) if nearest => distance < best,
+                Some((_, best)) => distance > best,
+            };
+            if is_better {
+                selected = Some((i as u32, distance));
+            }
+        }
+        let (city, _) = selected.unwrap();
+        let (edge_start, _) = tour.cheapest_edge(city);
+        tour.insert(edge_start, city);
+        for (i, distance) in distance_to_tour.iter_mut().enumerate() {
+            if !tour.in_tour[i] {
+                *distance = distance.min(cities[i].distance(&cities[city as usize]));
+            }
+        }
+    }
+    tour.into_solution(problem, start_index)
+}
+
+impl Solution {
+    /// Grows the tour from `start_index` by always inserting the city closest to it.
+    pub fn from_nearest_insertion(problem: &Rc<Problem>, start_index: usize) -> Self {
+        distance_driven_insertion(problem, start_index, true)
+    }
+
+    /// Grows the tour from `start_index` by always inserting the city farthest from it,
+    /// which sketches the overall shape first and fills in the details later.
+    pub fn from_farthest_insertion(problem: &Rc<Problem>, start_index: usize) -> Self {
+        distance_driven_insertion(problem, start_index, false)
+    }
+
+    /// Grows the tour from `start_index` by always making the insertion that adds the least
+    /// length. Every city remembers its cheapest edge; after an insertion only cities whose
+    /// edge was split need a full rescan, the rest just compare against the two new edges.
+    pub fn from_cheapest_insertion(problem: &Rc<Problem>, start_index: usize) -> Self {
+        assert!(start_index < problem.cities.len());
+        let cities = &problem.cities;
+        let mut tour = InsertionTour::new(cities, start_index);
+        let mut best_edge = vec![start_index as u32; cities.len()];
+        let mut best_cost = (0..cities.len() as u32)
+            .map(|city| tour.insertion_cost(start_index as u32, city))
+            .collect::<Vec<_>>();
+        for _ in 1..cities.len() {
+            let mut selected: Option<(u32, f32)> = None;
+            for (i, &cost) in best_cost.iter().enumerate() {
+                if !tour.in_tour[i] && selected.is_none_or(|(_, best)| cost < best) {
+                    selected = Some((i as u32, cost));
+                }
+            }
+            let (city, _) = selected.unwrap();
+            let split_edge = best_edge[city as usize];
+            tour.insert(split_edge, city);
+            for other in 0..cities.len() as u32 {
+                if tour.in_tour[other as usize] {
+                    continue;
+                }
+                if best_edge[other as usize] == split_edge {
+                    (best_edge[other as usize], best_cost[other as usize]) =
+                        tour.cheapest_edge(other);
+                    continue;
+                }
+                for new_edge in [split_edge, city] {
+                    let cost = tour.insertion_cost(new_edge, other);
+                    if cost < best_cost[other as usize] {
+                        best_edge[other as usize] = new_edge;
+                        best_cost[other as usize] = cost;
+                    }
+                }
+            }
+        }
+        tour.into_solution(problem, start_index)
+    }
+}
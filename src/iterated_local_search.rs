@@ -0,0 +1,175 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    construction::{Construction, build_start_tour},
+    genetic::DEFAULT_SEED,
+    local_search::{ActiveCities, LOCAL_SEARCH_NEIGHBORS, LocalSearch, deadline_passed, distance},
+    neighbors::nearest_neighbor_lists,
+    problem::{Problem, Solution},
+    tour::ArrayTour,
+};
+
+/// The longest of the two segments a kick exchanges. Short segments keep the kick local,
+/// so the local search that follows only has to repair a small part of the tour.
+pub const MAX_KICK_SEGMENT_LENGTH: usize = 50;
+
+/// When a tour found after a kick replaces the current one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Acceptance {
+    /// When it is not longer than the current tour.
+    BetterOrEqual,
+    /// When it is at most this fraction longer than the best tour found so far, so the
+    /// search can cross ridges between local optima.
+    Threshold(f64),
+}
+
+/// Iterated local search: a locally optimal tour is perturbed by a random double-bridge
+/// kick and repaired by the local searches, and the result replaces it under the
+/// acceptance rule. Kicks that are not accepted are undone.
+pub struct IteratedLocalSearch {
+    pub problem: Rc<Problem>,
+    /// Tours to start from, e.g. the result of a previous run.
+    pub initial_solutions: Vec<Solution>,
+    /// Heuristics that build start tours from the first city, next to the initial
+    /// solutions. The search starts from the shortest of all of them.
+    pub constructions: Vec<Construction>,
+    /// Local searches that repair the tour after every kick.
+    pub local_searches: Vec<LocalSearch>,
+    pub acceptance: Acceptance,
+    rng: RefCell<StdRng>,
+}
+
+impl IteratedLocalSearch {
+    pub fn new(problem: Rc<Problem>) -> Self {
+        Self::with_seed(problem, DEFAULT_SEED)
+    }
+
+    pub fn with_seed(problem: Rc<Problem>, seed: u64) -> Self {
+        Self {
+            problem,
+            initial_solutions: Vec::new(),
+            constructions: vec![Construction::GreedyEdge],
+            local_searches: vec![LocalSearch::TwoOpt, LocalSearch::OrOpt],
+            acceptance: Acceptance::BetterOrEqual,
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// Kicks and repairs the tour until `time_limit_secs` seconds after `timer` started,
+    /// and returns the best tour found.
+    pub fn solve(&self, timer: &Instant, time_limit_secs: u64) -> Solution {
+        let deadline = *timer + Duration::from_secs(time_limit_secs);
+        let problem = &self.problem;
+        let num_cities = problem.cities.len();
+        let start = build_start_tour(
+            problem,
+            &self.constructions,
+            &self.initial_solutions,
+            deadline,
+        );
+        let neighbor_lists = nearest_neighbor_lists(problem, LOCAL_SEARCH_NEIGHBORS);
+        let start = start.improve_with(&self.local_searches, &neighbor_lists, Some(deadline));
+        // too few cities for two segments that a kick could exchange usefully
+        if num_cities < 8 {
+            return start;
+        }
+        let first = start.order_without_loop[0];
+        let mut tour = ArrayTour::from_solution(&start);
+        let mut current_distance = start.total_distance() as f64;
+        let mut best_distance = current_distance;
+        let mut best_order = start.order_without_loop.clone();
+        let mut rng = self.rng.borrow_mut();
+        while !deadline_passed(Some(deadline)) {
+            tour.checkpoint();
+            let (kick_gain, endpoints) = double_bridge(&mut tour, problem, &mut *rng);
+            let gain =
+                kick_gain + self.repair(&mut tour, problem, &neighbor_lists, &endpoints, deadline);
+            let distance = current_distance - gain;
+            let accepted = match self.acceptance {
+                Acceptance::BetterOrEqual => distance <= current_distance,
+                Acceptance::Threshold(threshold) => distance <= best_distance * (1.0 + threshold),
+            };
+            if !accepted {
+                tour.rollback();
+                continue;
+            }
+            current_distance = distance;
+            if distance < best_distance {
+                best_distance = distance;
+                best_order.copy_from_slice(tour.order());
+            }
+        }
+        let position = best_order.iter().position(|&city| city == first).unwrap();
+        best_order.rotate_left(position);
+        Solution::new(best_order, Rc::downgrade(problem), None)
+    }
+
+    /// Runs the local searches from the `endpoints` of a kick, each one also from the
+    /// cities the others moved, until none of them improves the tour. Returns the gain.
+    fn repair(
+        &self,
+        tour: &mut ArrayTour,
+        problem: &Problem,
+        neighbor_lists: &[Vec<u32>],
+        endpoints: &[u32],
+        deadline: Instant,
+    ) -> f64 {
+        let mut changed = endpoints.to_vec();
+        let mut total_gain = 0.0;
+        loop {
+            let mut round_gain = 0.0;
+            for local_search in &self.local_searches {
+                let mut active = ActiveCities::only(tour.len(), &changed);
+                round_gain += local_search.improve(
+                    tour,
+                    problem,
+                    neighbor_lists,
+                    &mut active,
+                    Some(deadline),
+                );
+                changed.extend_from_slice(active.woken());
+            }
+            total_gain += round_gain;
+            if round_gain == 0.0 || self.local_searches.len() < 2 {
+                return total_gain;
+            }
+        }
+    }
+}
+
+/// The double-bridge kick: picks two neighboring segments of at most
+/// `MAX_KICK_SEGMENT_LENGTH` cities each at a random place and exchanges them. Searches
+/// built from 2-opt moves rarely undo it, since that takes moves through longer tours.
+/// Returns the gain, usually negative, and the endpoints of the six changed edges.
+fn double_bridge(tour: &mut ArrayTour, problem: &Problem, rng: &mut impl Rng) -> (f64, [u32; 6]) {
+    let n = tour.len();
+    let max_length = MAX_KICK_SEGMENT_LENGTH.min((n - 2) / 2);
+    let x_length = rng.random_range(1..=max_length);
+    let y_length = rng.random_range(1..=max_length);
+    let p = tour.order()[rng.random_range(0..n)];
+    let x_first = tour.next(p);
+    let mut x_last = x_first;
+    for _ in 1..x_length {
+        x_last = tour.next(x_last);
+    }
+    let y_first = tour.next(x_last);
+    let mut y_last = y_first;
+    for _ in 1..y_length {
+        y_last = tour.next(y_last);
+    }
+    let q = tour.next(y_last);
+    let gain = distance(problem, p, x_first)
+        + distance(problem, x_last, y_first)
+        + distance(problem, y_last, q)
+        - distance(problem, p, y_first)
+        - distance(problem, y_last, x_first)
+        - distance(problem, x_last, q);
+    tour.swap_segments(p, (x_first, x_last), (y_first, y_last), q, false, false);
+    (gain, [p, x_first, x_last, y_first, y_last, q])
+}
@@ -0,0 +1,113 @@
+use std::{
+    io::{self, Read, Write},
+    rc::Rc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    format::FormatError,
+    problem::{City, Problem, Solution},
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JsonCity {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+}
+
+/// The JSON form of a [`Problem`]. City ids and names are carried along for other
+/// tools; the solvers only look at the coordinates.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JsonProblem {
+    pub cities: Vec<JsonCity>,
+}
+
+impl JsonProblem {
+    pub fn from_reader(reader: impl Read) -> Result<Self, FormatError> {
+        serde_json::from_reader(reader).map_err(FormatError::Json)
+    }
+
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+
+    pub fn to_problem(&self) -> Problem {
+        Problem::new(
+            self.cities
+                .iter()
+                .map(|city| City::new(city.x, city.y, city.z))
+                .collect(),
+        )
+    }
+}
+
+impl From<&Problem> for JsonProblem {
+    fn from(problem: &Problem) -> Self {
+        let cities = problem
+            .cities
+            .iter()
+            .map(|city| JsonCity {
+                id: None,
+                name: None,
+                x: city.x,
+                y: city.y,
+                z: city.z,
+            })
+            .collect();
+        JsonProblem { cities }
+    }
+}
+
+/// A solved tour together with how it was obtained.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JsonSolution {
+    /// City indices in visiting order, without repeating the first city.
+    pub order: Vec<u32>,
+    pub total_distance: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solver: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elapsed_secs: Option<f64>,
+    /// Relative excess over a known optimum or lower bound, e.g. `0.02` for 2% above it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gap: Option<f64>,
+}
+
+impl JsonSolution {
+    pub fn new(solution: &Solution) -> Self {
+        JsonSolution {
+            order: solution.order_without_loop.clone(),
+            total_distance: solution.total_distance(),
+            solver: None,
+            seed: None,
+            elapsed_secs: None,
+            gap: None,
+        }
+    }
+
+    pub fn from_reader(reader: impl Read) -> Result<Self, FormatError> {
+        serde_json::from_reader(reader).map_err(FormatError::Json)
+    }
+
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+
+    /// The tour as a [`Solution`] of `problem`. The reported distance is not trusted,
+    /// so the length is recomputed on demand.
+    pub fn to_solution(&self, problem: &Rc<Problem>) -> Solution {
+        Solution::new(self.order.clone(), Rc::downgrade(problem), None)
+    }
+}
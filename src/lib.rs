@@ -1,7 +1,34 @@
 pub mod adaptive_solver;
+pub mod ant_colony;
+pub mod christofides;
+pub mod construction;
 pub mod cover_tree;
+pub mod crossover;
+pub mod edge_assembly;
+pub mod format;
 pub mod generator;
 pub mod genetic;
+pub mod greedy_edge;
+pub mod insertion;
+pub mod iterated_local_search;
+pub mod json;
 pub mod kdtree;
+pub mod lin_kernighan;
+pub mod local_search;
+pub mod mutation;
+pub mod neighbors;
+pub mod or_opt;
+pub mod partition_crossover;
 pub mod problem;
 pub mod random_perm;
+pub mod replacement;
+pub mod savings;
+pub mod selection;
+pub mod simulated_annealing;
+pub mod space_filling_curve;
+pub mod tabu_search;
+pub mod three_opt;
+pub mod tour;
+pub mod two_opt;
+pub mod verify;
+pub mod warm_start;
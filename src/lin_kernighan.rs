@@ -0,0 +1,165 @@
+use std::time::Instant;
+
+use crate::{
+    local_search::{ActiveCities, LocalSearch, deadline_passed, distance},
+    problem::{Problem, Solution},
+    tour::ArrayTour,
+    two_opt::MAX_FLIP_COST,
+};
+
+/// How many 2-opt steps one Lin-Kernighan move may chain.
+pub const LIN_KERNIGHAN_MAX_DEPTH: usize = 50;
+
+/// Steps that do not yet shorten the tour may reverse at most this many cities. Most of
+/// them are undone again, and on large instances long tentative flips would cost far more
+/// than they find.
+const TENTATIVE_MAX_FLIP_COST: usize = 1000;
+
+/// How many alternatives are tried for the first steps before giving up on a start; the
+/// later steps only follow the most promising one.
+const BREADTH: [usize; 2] = [5, 3];
+
+/// The state of one Lin-Kernighan move from a fixed `t1`.
+struct Move<'a> {
+    tour: &'a mut ArrayTour,
+    problem: &'a Problem,
+    neighbor_lists: &'a [Vec<u32>],
+    t1: u32,
+    /// The flips applied so far, to undo the steps past the best one.
+    flips: Vec<[u32; 4]>,
+    /// Edges added by this move, which it must not remove again.
+    added: Vec<(u32, u32)>,
+    best_gain: f64,
+    best_length: usize,
+}
+
+impl Move<'_> {
+    fn was_added(&self, a: u32, b: u32) -> bool {
+        self.added.contains(&(a, b)) || self.added.contains(&(b, a))
+    }
+
+    /// Extends the move by removing the edge `(t1, t2)`, after steps that gained
+    /// `gain` including the edge that closed the tour. Every step adds an edge from `t2`
+    /// to a candidate `t3` and removes `(t3, t4)`, the edge whose removal lets `(t4, t1)`
+    /// close the tour again, so every intermediate tour is valid. Returns whether an
+    /// improving tour has been found.
+    fn extend(&mut self, t2: u32, gain: f64, depth: usize) -> bool {
+        let t1 = self.t1;
+        let forward = self.tour.next(t1) == t2;
+        let removed_gain = gain + distance(self.problem, t1, t2);
+        let mut steps = Vec::new();
+        for &t3 in &self.neighbor_lists[t2 as usize] {
+            let g1 = removed_gain - distance(self.problem, t2, t3);
+            if g1 <= 0.0 {
+                break;
+            }
+            let t4 = self.tour.succ(t3, !forward);
+            if t3 == t1 || t4 == t2 || self.was_added(t3, t4) {
+                continue;
+            }
+            let open_gain = g1 + distance(self.problem, t3, t4);
+            let closed_gain = open_gain - distance(self.problem, t4, t1);
+            let max_flip_cost = if closed_gain > self.best_gain {
+                MAX_FLIP_COST
+            } else {
+                TENTATIVE_MAX_FLIP_COST
+            };
+            if self.tour.flip_cost(t1, t2, t4) > max_flip_cost {
+                continue;
+            }
+            steps.push((open_gain, t3, t4));
+        }
+        // the steps that remove the longest edge for the new one first
+        steps.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+        let breadth = BREADTH.get(depth).copied().unwrap_or(1);
+        for (open_gain, t3, t4) in steps.into_iter().take(breadth) {
+            self.tour.flip(t1, t2, t4, t3);
+            self.flips.push([t1, t2, t4, t3]);
+            self.added.push((t2, t3));
+            let closed_gain = open_gain - distance(self.problem, t4, t1);
+            if closed_gain > self.best_gain {
+                self.best_gain = closed_gain;
+                self.best_length = self.flips.len();
+            }
+            if depth + 1 < LIN_KERNIGHAN_MAX_DEPTH && self.extend(t4, closed_gain, depth + 1) {
+                return true;
+            }
+            if self.best_gain > 0.0 {
+                return true;
+            }
+            self.undo_last();
+        }
+        false
+    }
+
+    fn undo_last(&mut self) {
+        let [a, b, c, d] = self.flips.pop().unwrap();
+        self.added.pop();
+        // the flip added (a, c) and (b, d); flipping them back restores (a, b) and (c, d)
+        self.tour.flip(a, c, b, d);
+    }
+}
+
+/// Lin-Kernighan with candidate lists and don't-look bits. A move is a chain of up to
+/// `LIN_KERNIGHAN_MAX_DEPTH` 2-opt steps from a start city `t1`: each step replaces the
+/// edge that closes the tour at `t1` by an edge to a candidate city, as long as the
+/// removed edges still outweigh the added ones. The chain is cut back to the step with
+/// the shortest tour, and kept if that tour is shorter than before. Added edges are never
+/// removed again within a move. Stops at a local optimum or when `deadline` passes, and
+/// returns the total gain.
+pub fn lin_kernighan(
+    tour: &mut ArrayTour,
+    problem: &Problem,
+    neighbor_lists: &[Vec<u32>],
+    active: &mut ActiveCities,
+    deadline: Option<Instant>,
+) -> f64 {
+    let mut total_gain = 0.0;
+    if tour.len() < 5 {
+        return total_gain;
+    }
+    while let Some(t1) = active.pop() {
+        if deadline_passed(deadline) {
+            break;
+        }
+        for forward in [true, false] {
+            let t2 = tour.succ(t1, forward);
+            let mut lin_kernighan_move = Move {
+                tour: &mut *tour,
+                problem,
+                neighbor_lists,
+                t1,
+                flips: Vec::new(),
+                added: Vec::new(),
+                best_gain: 0.0,
+                best_length: 0,
+            };
+            lin_kernighan_move.extend(t2, 0.0, 0);
+            while lin_kernighan_move.flips.len() > lin_kernighan_move.best_length {
+                lin_kernighan_move.undo_last();
+            }
+            if lin_kernighan_move.best_gain > 0.0 {
+                total_gain += lin_kernighan_move.best_gain;
+                for flip in &lin_kernighan_move.flips {
+                    for &city in flip {
+                        active.push(city);
+                    }
+                }
+                active.push(t1);
+                break;
+            }
+        }
+    }
+    total_gain
+}
+
+impl Solution {
+    /// The tour improved by [`lin_kernighan`] until no move helps or `deadline` passes.
+    pub fn lin_kernighan(
+        &self,
+        neighbor_lists: &[Vec<u32>],
+        deadline: Option<Instant>,
+    ) -> Solution {
+        self.improve_with(&[LocalSearch::LinKernighan], neighbor_lists, deadline)
+    }
+}
@@ -0,0 +1,155 @@
+use std::{collections::VecDeque, rc::Rc, time::Instant};
+
+use crate::{
+    lin_kernighan::lin_kernighan,
+    neighbors::edge_length,
+    or_opt::or_opt,
+    problem::{Problem, Solution},
+    three_opt::three_opt,
+    tour::ArrayTour,
+    two_opt::two_opt,
+};
+
+/// How many nearest neighbors of every city the local searches try as new tour neighbors.
+pub const LOCAL_SEARCH_NEIGHBORS: usize = 10;
+
+/// The tour improvement heuristics that can polish a tour.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LocalSearch {
+    TwoOpt,
+    OrOpt,
+    ThreeOpt,
+    LinKernighan,
+}
+
+impl LocalSearch {
+    /// Improves `tour` with moves from the `active` cities until no move of this kind
+    /// helps or `deadline` passes, and returns by how much the tour got shorter.
+    pub fn improve(
+        &self,
+        tour: &mut ArrayTour,
+        problem: &Problem,
+        neighbor_lists: &[Vec<u32>],
+        active: &mut ActiveCities,
+        deadline: Option<Instant>,
+    ) -> f64 {
+        match self {
+            LocalSearch::TwoOpt => two_opt(tour, problem, neighbor_lists, active, deadline),
+            LocalSearch::OrOpt => or_opt(tour, problem, neighbor_lists, active, deadline),
+            LocalSearch::ThreeOpt => three_opt(tour, problem, neighbor_lists, active, deadline),
+            LocalSearch::LinKernighan => {
+                lin_kernighan(tour, problem, neighbor_lists, active, deadline)
+            }
+        }
+    }
+}
+
+/// The cities whose don't-look bit is off, in the order they will be looked at. A city
+/// is dropped once no improving move starts at it, and queued again when one of its tour
+/// edges changes.
+pub struct ActiveCities {
+    queue: VecDeque<u32>,
+    is_active: Vec<bool>,
+    /// Every city queued again, in order and with repetitions.
+    woken: Vec<u32>,
+}
+
+impl ActiveCities {
+    /// Every city of `tour`, in tour order.
+    pub fn all(tour: &ArrayTour) -> Self {
+        Self {
+            queue: tour.order().iter().copied().collect(),
+            is_active: vec![true; tour.len()],
+            woken: Vec::new(),
+        }
+    }
+
+    /// Only `cities`, e.g. the endpoints of a change to a locally optimal tour.
+    pub fn only(num_cities: usize, cities: &[u32]) -> Self {
+        let mut active = Self {
+            queue: VecDeque::new(),
+            is_active: vec![false; num_cities],
+            woken: Vec::new(),
+        };
+        for &city in cities {
+            active.push(city);
+        }
+        active.woken.clear();
+        active
+    }
+
+    pub fn push(&mut self, city: u32) {
+        self.woken.push(city);
+        if !self.is_active[city as usize] {
+            self.is_active[city as usize] = true;
+            self.queue.push_back(city);
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<u32> {
+        let city = self.queue.pop_front()?;
+        self.is_active[city as usize] = false;
+        Some(city)
+    }
+
+    /// The cities queued again since this set was created, i.e. the endpoints of every
+    /// move made.
+    pub fn woken(&self) -> &[u32] {
+        &self.woken
+    }
+}
+
+pub(crate) fn deadline_passed(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+/// Edge lengths in `f64`, so the gains of a move add up exactly and an improving move
+/// can never be undone by rounding.
+pub(crate) fn distance(problem: &Problem, a: u32, b: u32) -> f64 {
+    edge_length(problem, a, b) as f64
+}
+
+impl Solution {
+    /// Runs the `local_searches` one after the other until none of them improves the tour
+    /// any more or `deadline` passes. `neighbor_lists` are the candidate lists from
+    /// [`crate::neighbors::nearest_neighbor_lists`]. The tour keeps its first city and
+    /// its cached length is updated from the gains.
+    pub fn improve_with(
+        &self,
+        local_searches: &[LocalSearch],
+        neighbor_lists: &[Vec<u32>],
+        deadline: Option<Instant>,
+    ) -> Solution {
+        let problem = self.problem.upgrade().expect("Problem has been dropped");
+        let Some(&first) = self.order_without_loop.first() else {
+            return self.clone();
+        };
+        let mut tour = ArrayTour::from_solution(self);
+        let mut total_gain = 0.0;
+        loop {
+            let mut round_gain = 0.0;
+            for local_search in local_searches {
+                let mut active = ActiveCities::all(&tour);
+                round_gain += local_search.improve(
+                    &mut tour,
+                    &problem,
+                    neighbor_lists,
+                    &mut active,
+                    deadline,
+                );
+            }
+            total_gain += round_gain;
+            // don't-look bits only wake the endpoints of a move, so the tour is a local
+            // optimum once a round that starts with every city active finds nothing
+            if round_gain == 0.0 || deadline_passed(deadline) {
+                break;
+            }
+        }
+        let total_distance = (self.total_distance() as f64 - total_gain) as f32;
+        Solution::new(
+            tour.order_from(first),
+            Rc::downgrade(&problem),
+            Some(total_distance),
+        )
+    }
+}
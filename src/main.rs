@@ -1,12 +1,43 @@
-use std::{rc::Rc, time::Instant};
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use rand::{SeedableRng, rngs::StdRng};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use ordered_float::NotNan;
 use rust::{
-    adaptive_solver::adaptive_solve,
-    cover_tree::{CoverTree, Distance},
+    adaptive_solver::{
+        SolveOptions, adaptive_solve_with, ant_colony, class_time_limit, genetic_algorithm,
+        iterated_local_search, population_size, simulated_annealing, tabu_search,
+    },
+    ant_colony::AntSystem,
+    construction::Construction,
+    cover_tree::Distance,
+    crossover::{
+        Crossover, CycleCrossover, EdgeAssemblyCrossover, EdgeRecombinationCrossover,
+        OrderCrossover, PartiallyMappedCrossover, PartitionCrossover, PreciseCrossover,
+        RandomCrossover,
+    },
+    format::{FormatError, TourFile, create_output, open_input},
     generator::ProblemGenerator,
-    genetic::GeneticAlgorithm,
-    problem::{self, City, Problem, Solution},
+    genetic::DEFAULT_SEED,
+    iterated_local_search::Acceptance,
+    json::{JsonProblem, JsonSolution},
+    local_search::{LOCAL_SEARCH_NEIGHBORS, LocalSearch},
+    mutation::Mutation,
+    neighbors::nearest_neighbor_lists,
+    problem::{City, Problem, Solution},
+    replacement::{
+        CrowdingReplacement, DiversityReplacement, ElitistReplacement, TruncationReplacement,
+    },
+    selection::{
+        RankSelection, RouletteSelection, StochasticUniversalSampling, TournamentSelection,
+    },
+    simulated_annealing::CoolingSchedule,
+    verify::{DEFAULT_RELATIVE_TOLERANCE, verify_json_solution, verify_tour},
+    warm_start::{TourFormat, WarmStartError, load_tour},
 };
 
 // fn main() {
@@ -49,51 +80,807 @@ use rust::{
 //     // }
 // }
 
-use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
+#[derive(Parser)]
+#[command(about = "3D Euclidean TSP solver")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve an instance and write the tour in the homework output format.
+    Solve(Box<SolveArgs>),
+    /// Generate a random instance with cities inside a sphere.
+    Generate {
+        #[arg(short, long)]
+        num_cities: usize,
+        #[arg(short, long, default_value_t = 10000.0)]
+        max_radius: f32,
+        #[arg(short, long, default_value_t = DEFAULT_SEED)]
+        seed: u64,
+        /// Place the cities in this many clusters instead of uniformly.
+        #[arg(long)]
+        clusters: Option<usize>,
+        /// Radius of every cluster when `--clusters` is given.
+        #[arg(long, default_value_t = 1000.0)]
+        cluster_radius: f32,
+        /// Instance file, `-` for stdout. A `.json` file gets a JSON problem.
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Check that a tour file visits every city of an instance once and reports its length.
+    Validate {
+        #[arg(short, long, default_value = "input.txt")]
+        input: PathBuf,
+        #[arg(short, long, default_value = "output.txt")]
+        tour: PathBuf,
+        /// Allowed relative difference between the reported and the actual distance.
+        #[arg(long, default_value_t = DEFAULT_RELATIVE_TOLERANCE)]
+        tolerance: f64,
+    },
+    /// Print statistics about an instance.
+    Info {
+        #[arg(short, long, default_value = "input.txt")]
+        input: PathBuf,
+    },
+}
+
+#[derive(Args)]
+struct SolveArgs {
+    /// Instance file, `-` for stdin. A `.json` file is read as a JSON problem.
+    #[arg(short, long, default_value = "input.txt")]
+    input: PathBuf,
+    /// Tour file, `-` for stdout. A `.json` file gets a JSON result with solver details.
+    #[arg(short, long, default_value = "output.txt")]
+    output: PathBuf,
+    /// Time limit in seconds, measured from program start.
+    /// Defaults to the limit of the instance's size class, minus a safety margin.
+    #[arg(short, long)]
+    time_limit: Option<u64>,
+    #[arg(short, long, default_value_t = DEFAULT_SEED)]
+    seed: u64,
+    #[arg(long, value_enum, default_value_t = SolverChoice::Auto)]
+    solver: SolverChoice,
+    /// Tour files to continue from, e.g. the output of a previous run. May be repeated.
+    /// `.json` files are read as JSON results.
+    #[arg(short, long)]
+    warm_start: Vec<PathBuf>,
+    #[arg(long, value_enum, default_value_t = TourFormatChoice::Auto)]
+    warm_start_format: TourFormatChoice,
+    /// Heuristics that build the initial population of the genetic algorithm, or the start
+    /// tour of the other solvers. May be repeated. Without it, every solver uses its own,
+    /// e.g. nearest neighbor for the genetic algorithm and greedy edge for iterated local
+    /// search.
+    #[arg(short, long, value_enum)]
+    construction: Vec<ConstructionChoice>,
+    /// Local searches that polish the constructed tours, run in turn until none improves.
+    /// May be repeated.
+    #[arg(short, long, value_enum)]
+    local_search: Vec<LocalSearchChoice>,
+    /// Local searches that improve the children of the genetic algorithm before they join
+    /// the population. May be repeated.
+    #[arg(long, value_enum)]
+    offspring_local_search: Vec<LocalSearchChoice>,
+    /// The share of children that the offspring local searches improve, from 0 to 1.
+    /// All of them by default.
+    #[arg(long, value_parser = parse_share)]
+    offspring_local_search_probability: Option<f64>,
+    /// A crossover of the genetic algorithm as NAME or NAME:WEIGHT, e.g. `edge-assembly:2`.
+    /// Each one is drawn with a probability proportional to its weight, 1 by default.
+    /// May be repeated. Without it, the genetic algorithm uses its own mix.
+    #[arg(long, value_parser = parse_weighted_crossover)]
+    crossover: Vec<(CrossoverChoice, f64)>,
+    /// A mutation of the genetic algorithm as NAME:RATE, e.g. `double-bridge:0.1`, where
+    /// RATE is the share of children it changes, from 0 to 1. May be repeated.
+    #[arg(long, value_parser = parse_mutation_rate)]
+    mutation: Vec<(MutationChoice, f64)>,
+    /// How the genetic algorithm picks parents. Roulette by default.
+    #[arg(long, value_enum)]
+    selection: Option<SelectionChoice>,
+    /// How many tours compete in a tournament of tournament selection, at most the
+    /// population size. 3 by default.
+    #[arg(long)]
+    tournament_size: Option<usize>,
+    /// How strongly rank selection prefers short tours, from 1 (uniform) to 2. 1.5 by
+    /// default.
+    #[arg(long, value_parser = parse_rank_pressure)]
+    rank_pressure: Option<f64>,
+    /// How the genetic algorithm picks the survivors of a generation. Elitist by default.
+    #[arg(long, value_enum)]
+    replacement: Option<ReplacementChoice>,
+    /// How many of the shortest tours survive elitist replacement. Half of the
+    /// population by default.
+    #[arg(long)]
+    elite_count: Option<usize>,
+    /// The smallest share of edges in which a survivor of diversity replacement differs
+    /// from every shorter one. 0.05 by default.
+    #[arg(long, value_parser = parse_share)]
+    min_edge_distance: Option<f64>,
+    /// Lets iterated local search accept tours up to this fraction longer than the best one
+    /// found, e.g. 0.01. Without it, only tours that are not longer than the current one
+    /// are accepted.
+    #[arg(long)]
+    acceptance_threshold: Option<f64>,
+    /// How simulated annealing lowers the temperature. Geometric by default.
+    #[arg(long, value_enum)]
+    cooling_schedule: Option<CoolingScheduleChoice>,
+    /// How many times simulated annealing raises the temperature again, restarting from
+    /// the best tour. The time limit is split evenly between the cycles. None by default.
+    #[arg(long)]
+    reheats: Option<u32>,
+    /// Known optimal or best known tour length, used to report the gap in JSON output.
+    #[arg(long)]
+    reference_distance: Option<f64>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TourFormatChoice {
+    /// Detect the format from the file content.
+    Auto,
+    /// Total distance followed by the coordinates of every city, as written by `solve`.
+    Coordinates,
+    /// One 0-based city index per line.
+    Indices,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ConstructionChoice {
+    NearestNeighbor,
+    NearestInsertion,
+    CheapestInsertion,
+    FarthestInsertion,
+    GreedyEdge,
+    HilbertCurve,
+    MortonOrder,
+    Christofides,
+    Savings,
+}
+
+impl From<ConstructionChoice> for Construction {
+    fn from(choice: ConstructionChoice) -> Self {
+        match choice {
+            ConstructionChoice::NearestNeighbor => Construction::NearestNeighbor,
+            ConstructionChoice::NearestInsertion => Construction::NearestInsertion,
+            ConstructionChoice::CheapestInsertion => Construction::CheapestInsertion,
+            ConstructionChoice::FarthestInsertion => Construction::FarthestInsertion,
+            ConstructionChoice::GreedyEdge => Construction::GreedyEdge,
+            ConstructionChoice::HilbertCurve => Construction::HilbertCurve,
+            ConstructionChoice::MortonOrder => Construction::MortonOrder,
+            ConstructionChoice::Christofides => Construction::Christofides,
+            ConstructionChoice::Savings => Construction::Savings,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LocalSearchChoice {
+    TwoOpt,
+    OrOpt,
+    ThreeOpt,
+    LinKernighan,
+}
+
+impl From<LocalSearchChoice> for LocalSearch {
+    fn from(choice: LocalSearchChoice) -> Self {
+        match choice {
+            LocalSearchChoice::TwoOpt => LocalSearch::TwoOpt,
+            LocalSearchChoice::OrOpt => LocalSearch::OrOpt,
+            LocalSearchChoice::ThreeOpt => LocalSearch::ThreeOpt,
+            LocalSearchChoice::LinKernighan => LocalSearch::LinKernighan,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CrossoverChoice {
+    Random,
+    Precise,
+    EdgeAssembly,
+    Partition,
+    Order,
+    PartiallyMapped,
+    Cycle,
+    EdgeRecombination,
+}
+
+impl From<CrossoverChoice> for Box<dyn Crossover> {
+    fn from(choice: CrossoverChoice) -> Self {
+        match choice {
+            CrossoverChoice::Random => Box::new(RandomCrossover),
+            CrossoverChoice::Precise => Box::new(PreciseCrossover),
+            CrossoverChoice::EdgeAssembly => Box::new(EdgeAssemblyCrossover),
+            CrossoverChoice::Partition => Box::new(PartitionCrossover),
+            CrossoverChoice::Order => Box::new(OrderCrossover),
+            CrossoverChoice::PartiallyMapped => Box::new(PartiallyMappedCrossover),
+            CrossoverChoice::Cycle => Box::new(CycleCrossover),
+            CrossoverChoice::EdgeRecombination => Box::new(EdgeRecombinationCrossover),
+        }
+    }
+}
+
+/// Parses `NAME` or `NAME:WEIGHT`.
+fn parse_weighted_crossover(arg: &str) -> Result<(CrossoverChoice, f64), String> {
+    let (name, weight) = match arg.split_once(':') {
+        Some((name, weight)) => (
+            name,
+            weight
+                .parse::<f64>()
+                .map_err(|e| format!("invalid weight {}: {}", weight, e))?,
+        ),
+        None => (arg, 1.0),
+    };
+    if !(weight.is_finite() && weight > 0.0) {
+        return Err(format!("weight {} is not positive", weight));
+    }
+    Ok((CrossoverChoice::from_str(name, true)?, weight))
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MutationChoice {
+    Inversion,
+    Swap,
+    Insertion,
+    Scramble,
+    DoubleBridge,
+}
+
+impl From<MutationChoice> for Mutation {
+    fn from(choice: MutationChoice) -> Self {
+        match choice {
+            MutationChoice::Inversion => Mutation::Inversion,
+            MutationChoice::Swap => Mutation::Swap,
+            MutationChoice::Insertion => Mutation::Insertion,
+            MutationChoice::Scramble => Mutation::Scramble,
+            MutationChoice::DoubleBridge => Mutation::DoubleBridge,
+        }
+    }
+}
+
+/// Parses `NAME:RATE`.
+fn parse_mutation_rate(arg: &str) -> Result<(MutationChoice, f64), String> {
+    let (name, rate) = arg
+        .split_once(':')
+        .ok_or_else(|| format!("expected NAME:RATE, got {}", arg))?;
+    let rate = rate
+        .parse::<f64>()
+        .map_err(|e| format!("invalid rate {}: {}", rate, e))?;
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(format!("rate {} is not between 0 and 1", rate));
+    }
+    Ok((MutationChoice::from_str(name, true)?, rate))
+}
+
+/// Parses a number from `min` to `max`.
+fn parse_in_range(arg: &str, min: f64, max: f64) -> Result<f64, String> {
+    let value = arg
+        .parse::<f64>()
+        .map_err(|e| format!("invalid number {}: {}", arg, e))?;
+    if !(min..=max).contains(&value) {
+        return Err(format!("{} is not between {} and {}", value, min, max));
+    }
+    Ok(value)
+}
 
-fn main() {
-    let input_file = File::open("../test_cases/test_case11.txt").expect("Failed to open input file");
-    let mut reader = BufReader::new(input_file);
+fn parse_share(arg: &str) -> Result<f64, String> {
+    parse_in_range(arg, 0.0, 1.0)
+}
 
-    let mut first_line = String::new();
-    reader.read_line(&mut first_line).unwrap();
-    let n: u32 = first_line.trim().parse().unwrap();
+fn parse_rank_pressure(arg: &str) -> Result<f64, String> {
+    parse_in_range(arg, 1.0, 2.0)
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SelectionChoice {
+    /// Fitness-proportional roulette selection.
+    Roulette,
+    /// The shortest of `--tournament-size` random tours.
+    Tournament,
+    /// Linear rank selection with `--rank-pressure`.
+    Rank,
+    /// Stochastic universal sampling of the roulette fitness.
+    StochasticUniversal,
+}
 
-    println!("Number of cities: {}", n);
+#[derive(Clone, Copy, ValueEnum)]
+enum ReplacementChoice {
+    /// The shortest tours of the population and the children, (μ+λ).
+    Truncation,
+    /// The `--elite-count` shortest tours and random others.
+    Elitist,
+    /// Every child replaces its most similar parent when it is shorter.
+    Crowding,
+    /// The shortest tours that differ in `--min-edge-distance` of their edges.
+    Diversity,
+}
 
-    let mut cities = Vec::with_capacity(n as usize);
-    for _ in 0..n {
-        let mut line = String::new();
-        reader.read_line(&mut line).unwrap();
-        let coords: Vec<u32> = line
-            .split_whitespace()
-            .map(|s| s.parse::<u32>().unwrap())
-            .collect();
-        let (x, y, z) = (coords[0], coords[1], coords[2]);
-        cities.push(City::new(x, y, z)); // Assumes City::new(x, y, z) exists
+#[derive(Clone, Copy, ValueEnum)]
+enum CoolingScheduleChoice {
+    /// Lower the temperature geometrically over the time limit.
+    Geometric,
+    /// Steer the temperature towards a falling share of accepted uphill moves.
+    Adaptive,
+}
+
+impl From<CoolingScheduleChoice> for CoolingSchedule {
+    fn from(choice: CoolingScheduleChoice) -> Self {
+        match choice {
+            CoolingScheduleChoice::Geometric => CoolingSchedule::Geometric,
+            CoolingScheduleChoice::Adaptive => CoolingSchedule::Adaptive,
+        }
     }
+}
 
-    let problem = Rc::new(Problem::new(cities)); // Assumes Problem::new(cities) exists
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum SolverChoice {
+    /// Pick a solver by instance size.
+    Auto,
+    Genetic,
+    IteratedLocalSearch,
+    SimulatedAnnealing,
+    TabuSearch,
+    AntColonySystem,
+    MaxMinAntSystem,
+    NearestNeighbor,
+    NearestInsertion,
+    CheapestInsertion,
+    FarthestInsertion,
+    GreedyEdge,
+    HilbertCurve,
+    MortonOrder,
+    Christofides,
+    Savings,
+    BruteForce,
+}
 
-    let mut timer = Instant::now(); // Assumes Timer::new() and start() exist
+/// Files ending in `.json` use the JSON formats, everything else the homework formats.
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
 
-    let solution = adaptive_solve(Rc::clone(&problem), &timer);
+fn read_problem(path: &Path) -> Result<Problem, String> {
+    let reader = open_input(path).map_err(FormatError::from);
+    if is_json(path) {
+        reader
+            .and_then(JsonProblem::from_reader)
+            .map(|problem| problem.to_problem())
+    } else {
+        reader.and_then(Problem::from_reader)
+    }
+    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
 
-    let mut output_file = File::create("output.txt").expect("Failed to create output file");
+fn solve(
+    problem: Rc<Problem>,
+    timer: &Instant,
+    options: SolveOptions,
+    solver: SolverChoice,
+) -> Solution {
+    let num_cities = problem.cities.len();
+    if num_cities < 3 {
+        // every order is optimal, and the other solvers assume at least a few cities
+        return Solution::from_brute_force(&problem);
+    }
+    let warm_start = options
+        .initial_solutions
+        .iter()
+        .min_by_key(|solution| NotNan::new(solution.total_distance()).unwrap())
+        .cloned();
+    let time_limit_secs = options
+        .time_limit_secs
+        .unwrap_or_else(|| class_time_limit(num_cities));
+    let local_searches = options.local_searches.clone();
+    let solution = match solver {
+        SolverChoice::Auto => adaptive_solve_with(problem, timer, options),
+        SolverChoice::Genetic => genetic_algorithm(problem, timer, time_limit_secs, options),
+        SolverChoice::IteratedLocalSearch => {
+            iterated_local_search(problem, timer, time_limit_secs, options)
+        }
+        SolverChoice::SimulatedAnnealing => {
+            simulated_annealing(problem, timer, time_limit_secs, options)
+        }
+        SolverChoice::TabuSearch => tabu_search(problem, timer, time_limit_secs, options),
+        SolverChoice::AntColonySystem => ant_colony(
+            problem,
+            timer,
+            time_limit_secs,
+            AntSystem::AntColonySystem,
+            options,
+        ),
+        SolverChoice::MaxMinAntSystem => ant_colony(
+            problem,
+            timer,
+            time_limit_secs,
+            AntSystem::MaxMinAntSystem,
+            options,
+        ),
+        SolverChoice::NearestNeighbor => Solution::from_nearest_neighbor(&problem, 0),
+        SolverChoice::NearestInsertion => Solution::from_nearest_insertion(&problem, 0),
+        SolverChoice::CheapestInsertion => Solution::from_cheapest_insertion(&problem, 0),
+        SolverChoice::FarthestInsertion => Solution::from_farthest_insertion(&problem, 0),
+        SolverChoice::GreedyEdge => Solution::from_greedy_edge(&problem, 0),
+        SolverChoice::HilbertCurve => Solution::from_hilbert_curve(&problem, 0),
+        SolverChoice::MortonOrder => Solution::from_morton_order(&problem, 0),
+        SolverChoice::Christofides => Solution::from_christofides(&problem, 0),
+        SolverChoice::Savings => Solution::from_savings(&problem, 0),
+        SolverChoice::BruteForce => Solution::from_brute_force(&problem),
+    };
+    // the metaheuristics use the whole time limit and brute force is already optimal
+    let solution = match solver {
+        SolverChoice::Auto
+        | SolverChoice::Genetic
+        | SolverChoice::IteratedLocalSearch
+        | SolverChoice::SimulatedAnnealing
+        | SolverChoice::TabuSearch
+        | SolverChoice::AntColonySystem
+        | SolverChoice::MaxMinAntSystem
+        | SolverChoice::BruteForce => solution,
+        _ if local_searches.is_empty() => solution,
+        _ => {
+            let problem = solution.problem.upgrade().unwrap();
+            let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+            let deadline = *timer + Duration::from_secs(time_limit_secs);
+            let polished = solution.improve_with(&local_searches, &neighbor_lists, Some(deadline));
+            eprintln!(
+                "Local search: {} -> {}",
+                solution.total_distance(),
+                polished.total_distance()
+            );
+            polished
+        }
+    };
+    // never hand back a tour worse than the one we started from
+    match warm_start {
+        Some(warm_start) if warm_start.total_distance() < solution.total_distance() => warm_start,
+        _ => solution,
+    }
+}
+
+fn read_warm_start(
+    problem: &Rc<Problem>,
+    path: &Path,
+    format: TourFormatChoice,
+) -> Result<Solution, String> {
+    let format = match format {
+        TourFormatChoice::Auto => TourFormat::Auto,
+        TourFormatChoice::Coordinates => TourFormat::Coordinates,
+        TourFormatChoice::Indices => TourFormat::Indices,
+    };
+    let reader = open_input(path).map_err(WarmStartError::from);
+    if is_json(path) {
+        let solution = reader
+            .and_then(|reader| JsonSolution::from_reader(reader).map_err(WarmStartError::from))
+            .map(|json_solution| json_solution.to_solution(problem))
+            .map_err(|e| format!("Failed to load warm start {}: {}", path.display(), e))?;
+        if !solution.is_valid(problem.cities.len() as u32) {
+            return Err(format!(
+                "Warm start {} does not visit every city exactly once",
+                path.display()
+            ));
+        }
+        return Ok(solution);
+    }
+    reader
+        .and_then(|reader| load_tour(problem, reader, format))
+        .map_err(|e| format!("Failed to load warm start {}: {}", path.display(), e))
+}
+
+fn run_solve(args: SolveArgs) -> Result<(), String> {
+    let timer = Instant::now();
+    // flags of a single solver, which the other solvers would silently ignore
+    let solver_flags = [
+        (
+            "--offspring-local-search",
+            !args.offspring_local_search.is_empty(),
+            SolverChoice::Genetic,
+        ),
+        (
+            "--offspring-local-search-probability",
+            args.offspring_local_search_probability.is_some(),
+            SolverChoice::Genetic,
+        ),
+        (
+            "--crossover",
+            !args.crossover.is_empty(),
+            SolverChoice::Genetic,
+        ),
+        (
+            "--mutation",
+            !args.mutation.is_empty(),
+            SolverChoice::Genetic,
+        ),
+        (
+            "--selection",
+            args.selection.is_some(),
+            SolverChoice::Genetic,
+        ),
+        (
+            "--tournament-size",
+            args.tournament_size.is_some(),
+            SolverChoice::Genetic,
+        ),
+        (
+            "--rank-pressure",
+            args.rank_pressure.is_some(),
+            SolverChoice::Genetic,
+        ),
+        (
+            "--replacement",
+            args.replacement.is_some(),
+            SolverChoice::Genetic,
+        ),
+        (
+            "--elite-count",
+            args.elite_count.is_some(),
+            SolverChoice::Genetic,
+        ),
+        (
+            "--min-edge-distance",
+            args.min_edge_distance.is_some(),
+            SolverChoice::Genetic,
+        ),
+        (
+            "--acceptance-threshold",
+            args.acceptance_threshold.is_some(),
+            SolverChoice::IteratedLocalSearch,
+        ),
+        (
+            "--cooling-schedule",
+            args.cooling_schedule.is_some(),
+            SolverChoice::SimulatedAnnealing,
+        ),
+        (
+            "--reheats",
+            args.reheats.is_some(),
+            SolverChoice::SimulatedAnnealing,
+        ),
+    ];
+    for (flag, given, solver) in solver_flags {
+        if given && args.solver != solver {
+            let name = solver.to_possible_value().unwrap();
+            return Err(format!(
+                "{} only applies to --solver {}",
+                flag,
+                name.get_name()
+            ));
+        }
+    }
+    let problem = Rc::new(read_problem(&args.input)?);
+    eprintln!("Number of cities: {}", problem.cities.len());
+    let max_tournament_size = population_size(problem.cities.len());
+    if let Some(size) = args.tournament_size
+        && !(1..=max_tournament_size).contains(&size)
+    {
+        return Err(format!(
+            "--tournament-size {} is not between 1 and the population size {}",
+            size, max_tournament_size
+        ));
+    }
+    let initial_solutions = args
+        .warm_start
+        .iter()
+        .map(|path| read_warm_start(&problem, path, args.warm_start_format))
+        .collect::<Result<Vec<_>, _>>()?;
+    let options = SolveOptions {
+        time_limit_secs: args.time_limit,
+        seed: args.seed,
+        initial_solutions,
+        constructions: args
+            .construction
+            .into_iter()
+            .map(Construction::from)
+            .collect(),
+        local_searches: args
+            .local_search
+            .into_iter()
+            .map(LocalSearch::from)
+            .collect(),
+        offspring_local_searches: args
+            .offspring_local_search
+            .into_iter()
+            .map(LocalSearch::from)
+            .collect(),
+        offspring_local_search_probability: args.offspring_local_search_probability.unwrap_or(1.0),
+        crossovers: args
+            .crossover
+            .into_iter()
+            .map(|(crossover, weight)| (crossover.into(), weight))
+            .collect(),
+        mutations: args
+            .mutation
+            .into_iter()
+            .map(|(mutation, rate)| (mutation.into(), rate))
+            .collect(),
+        selection: Some(match args.selection.unwrap_or(SelectionChoice::Roulette) {
+            SelectionChoice::Roulette => Rc::new(RouletteSelection),
+            SelectionChoice::Tournament => Rc::new(TournamentSelection {
+                size: args.tournament_size.unwrap_or(3),
+            }),
+            SelectionChoice::Rank => Rc::new(RankSelection {
+                pressure: args.rank_pressure.unwrap_or(1.5),
+            }),
+            SelectionChoice::StochasticUniversal => Rc::new(StochasticUniversalSampling),
+        }),
+        replacement: Some(
+            match args.replacement.unwrap_or(ReplacementChoice::Elitist) {
+                ReplacementChoice::Truncation => Box::new(TruncationReplacement),
+                ReplacementChoice::Elitist => Box::new(ElitistReplacement {
+                    elite_count: args.elite_count,
+                }),
+                ReplacementChoice::Crowding => Box::new(CrowdingReplacement),
+                ReplacementChoice::Diversity => Box::new(DiversityReplacement {
+                    min_distance: args.min_edge_distance.unwrap_or(0.05),
+                }),
+            },
+        ),
+        acceptance: match args.acceptance_threshold {
+            Some(threshold) => Acceptance::Threshold(threshold),
+            None => Acceptance::BetterOrEqual,
+        },
+        cooling_schedule: args
+            .cooling_schedule
+            .unwrap_or(CoolingScheduleChoice::Geometric)
+            .into(),
+        reheats: args.reheats.unwrap_or(0),
+    };
+    let solution = solve(Rc::clone(&problem), &timer, options, args.solver);
+    create_output(&args.output)
+        .and_then(|writer| {
+            if !is_json(&args.output) {
+                return solution.write_to(writer);
+            }
+            let mut json_solution = JsonSolution::new(&solution);
+            json_solution.solver = args
+                .solver
+                .to_possible_value()
+                .map(|value| value.get_name().to_string());
+            json_solution.seed = Some(args.seed);
+            json_solution.elapsed_secs = Some(timer.elapsed().as_secs_f64());
+            json_solution.gap = args.reference_distance.map(|reference_distance| {
+                (solution.total_distance() as f64 - reference_distance) / reference_distance
+            });
+            json_solution.write_to(writer)
+        })
+        .map_err(|e| format!("Failed to write {}: {}", args.output.display(), e))?;
+    eprintln!("Time used: {} seconds", timer.elapsed().as_secs());
+    eprintln!("Best distance: {}", solution.total_distance());
+    Ok(())
+}
 
-    writeln!(output_file, "{}", solution.total_distance()).unwrap();
+fn run_generate(
+    num_cities: usize,
+    max_radius: f32,
+    seed: u64,
+    clusters: Option<(usize, f32)>,
+    output: &Path,
+) -> Result<(), String> {
+    let generator = ProblemGenerator::new(num_cities, max_radius, seed);
+    let problem = match clusters {
+        Some((num_clusters, cluster_radius)) => {
+            if num_clusters == 0 || cluster_radius > max_radius {
+                return Err("Need at least one cluster, no larger than the max radius".to_string());
+            }
+            generator.generate_clustered_problem(num_clusters, cluster_radius)
+        }
+        None => generator.generate_problem(),
+    };
+    create_output(output)
+        .and_then(|writer| {
+            if is_json(output) {
+                JsonProblem::from(&problem).write_to(writer)
+            } else {
+                problem.write_to(writer)
+            }
+        })
+        .map_err(|e| format!("Failed to write {}: {}", output.display(), e))
+}
 
-    for &city_index in &solution.order_without_loop {
-        let city = &problem.cities[city_index as usize];
-        writeln!(output_file, "{} {} {}", city.x, city.y, city.z).unwrap();
+fn run_validate(input: &Path, tour: &Path, tolerance: f64) -> Result<(), String> {
+    let problem = read_problem(input)?;
+    let reader = open_input(tour).map_err(FormatError::from);
+    let report = if is_json(tour) {
+        reader
+            .and_then(JsonSolution::from_reader)
+            .map(|solution| verify_json_solution(&problem, &solution, tolerance))
+    } else {
+        reader
+            .and_then(TourFile::from_reader)
+            .map(|tour_file| verify_tour(&problem, &tour_file, tolerance))
+    }
+    .map_err(|e| format!("Failed to read {}: {}", tour.display(), e))?;
+    if report.is_valid() {
+        println!("Valid tour of length {}", report.actual_distance);
+        return Ok(());
+    }
+    for issue in &report.issues {
+        println!("{}: {}", tour.display(), issue);
     }
+    Err(format!(
+        "{} is not a valid tour: {} issue(s) found",
+        tour.display(),
+        report.issues.len()
+    ))
+}
 
-    if let Some(&first_index) = solution.order_without_loop.first() {
-        let city = &problem.cities[first_index as usize];
-        writeln!(output_file, "{} {} {}", city.x, city.y, city.z).unwrap();
+fn run_info(input: &Path) -> Result<(), String> {
+    let problem = Rc::new(read_problem(input)?);
+    let cities = &problem.cities;
+    println!("Number of cities: {}", cities.len());
+    if cities.is_empty() {
+        return Ok(());
+    }
+    let distinct = cities
+        .iter()
+        .collect::<std::collections::HashSet<_>>()
+        .len();
+    println!("Duplicate cities: {}", cities.len() - distinct);
+    let min = |f: fn(&City) -> u32| cities.iter().map(f).min().unwrap();
+    let max = |f: fn(&City) -> u32| cities.iter().map(f).max().unwrap();
+    println!(
+        "Bounding box: ({}, {}, {}) to ({}, {}, {})",
+        min(|c| c.x),
+        min(|c| c.y),
+        min(|c| c.z),
+        max(|c| c.x),
+        max(|c| c.y),
+        max(|c| c.z)
+    );
+    let mean =
+        |f: fn(&City) -> u32| cities.iter().map(|c| f(c) as f64).sum::<f64>() / cities.len() as f64;
+    println!(
+        "Centroid: ({:.1}, {:.1}, {:.1})",
+        mean(|c| c.x),
+        mean(|c| c.y),
+        mean(|c| c.z)
+    );
+    let centroid = City::new(
+        mean(|c| c.x).round() as u32,
+        mean(|c| c.y).round() as u32,
+        mean(|c| c.z).round() as u32,
+    );
+    let radius = cities
+        .iter()
+        .map(|c| c.distance(&centroid))
+        .fold(0.0, f32::max);
+    println!("Max distance from centroid: {:.1}", radius);
+    if cities.len() >= 3 {
+        let nearest_neighbor = Solution::from_nearest_neighbor(&problem, 0);
+        println!(
+            "Nearest neighbor tour length from city 0: {}",
+            nearest_neighbor.total_distance()
+        );
     }
+    Ok(())
+}
 
-    println!("Time used: {} seconds", timer.elapsed().as_secs());
-    println!("Best distance: {}", solution.total_distance());
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Solve(args) => run_solve(*args),
+        Command::Generate {
+            num_cities,
+            max_radius,
+            seed,
+            clusters,
+            cluster_radius,
+            output,
+        } => {
+            let clusters = clusters.map(|num_clusters| (num_clusters, cluster_radius));
+            run_generate(num_cities, max_radius, seed, clusters, &output)
+        }
+        Command::Validate {
+            input,
+            tour,
+            tolerance,
+        } => run_validate(&input, &tour, tolerance),
+        Command::Info { input } => run_info(&input),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,104 @@
+use rand::{Rng, seq::SliceRandom};
+
+/// How many cities a scramble mutation shuffles at most, so that it stays a local change
+/// on large instances.
+pub const MAX_SCRAMBLE_LENGTH: usize = 8;
+
+/// The random changes that the genetic algorithm can make to its children.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// Reverses a random segment, which changes two edges like a 2-opt move.
+    Inversion,
+    /// Exchanges two random cities.
+    Swap,
+    /// Moves a random city to another random position.
+    Insertion,
+    /// Shuffles a random segment of at most `MAX_SCRAMBLE_LENGTH` cities.
+    Scramble,
+    /// Cuts the tour into four parts A B C D at random and reconnects them as A C B D,
+    /// which exchanges two segments without reversing either, so 2-opt moves rarely
+    /// undo it.
+    DoubleBridge,
+}
+
+impl Mutation {
+    /// Applies the mutation to `order`. Tours of fewer than four cities stay as they are.
+    pub fn mutate(&self, order: &mut [u32], rng: &mut impl Rng) {
+        let n = order.len();
+        if n < 4 {
+            return;
+        }
+        match self {
+            Mutation::Inversion => {
+                let (start, end) = random_pair(n, rng);
+                order[start..=end].reverse();
+            }
+            Mutation::Swap => {
+                let (i, j) = random_pair(n, rng);
+                order.swap(i, j);
+            }
+            Mutation::Insertion => {
+                let from = rng.random_range(0..n);
+                let to = loop {
+                    let to = rng.random_range(0..n);
+                    if to != from {
+                        break to;
+                    }
+                };
+                if from < to {
+                    order[from..=to].rotate_left(1);
+                } else {
+                    order[to..=from].rotate_right(1);
+                }
+            }
+            Mutation::Scramble => {
+                let length = rng.random_range(2..=MAX_SCRAMBLE_LENGTH.min(n));
+                let start = rng.random_range(0..=n - length);
+                order[start..start + length].shuffle(rng);
+            }
+            Mutation::DoubleBridge => {
+                // three cuts after the first city, so that every part is non-empty
+                let mut cuts = [0; 3];
+                loop {
+                    for cut in &mut cuts {
+                        *cut = rng.random_range(1..n);
+                    }
+                    cuts.sort_unstable();
+                    if cuts[0] < cuts[1] && cuts[1] < cuts[2] {
+                        break;
+                    }
+                }
+                let [b, c, d] = cuts;
+                // A B C D -> A C B D
+                order[b..d].rotate_left(c - b);
+            }
+        }
+    }
+}
+
+/// Draws every mutation of `mutations` with its rate, the share of children it changes,
+/// and applies the drawn ones in turn to a copy of `order`. `None` when none was drawn.
+pub fn mutate_with_rates(
+    mutations: &[(Mutation, f64)],
+    order: &[u32],
+    rng: &mut impl Rng,
+) -> Option<Vec<u32>> {
+    let mut mutated: Option<Vec<u32>> = None;
+    for &(mutation, rate) in mutations {
+        if rng.random::<f64>() < rate {
+            mutation.mutate(mutated.get_or_insert_with(|| order.to_vec()), rng);
+        }
+    }
+    mutated
+}
+
+/// Two different random positions in a tour of `n` cities, the smaller first.
+fn random_pair(n: usize, rng: &mut impl Rng) -> (usize, usize) {
+    loop {
+        let i = rng.random_range(0..n);
+        let j = rng.random_range(0..n);
+        if i != j {
+            return (i.min(j), i.max(j));
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::BinaryHeap;
+
+use crate::{
+    cover_tree::Distance,
+    problem::{City, Problem},
+};
+
+/// Average number of cities per cell of the grid behind [`nearest_neighbor_lists`].
+const CITIES_PER_CELL: f64 = 2.0;
+
+/// A uniform grid over the bounding box of the cities, with the cities of every cell
+/// stored contiguously.
+pub struct Grid {
+    min: [u32; 3],
+    cell_size: f64,
+    dims: [usize; 3],
+    cell_start: Vec<u32>,
+    cities: Vec<u32>,
+}
+
+impl Grid {
+    pub fn new(cities: &[City]) -> Self {
+        let axes = |city: &City| [city.x, city.y, city.z];
+        let mut min = [u32::MAX; 3];
+        let mut max = [0u32; 3];
+        for city in cities {
+            for (axis, value) in axes(city).into_iter().enumerate() {
+                min[axis] = min[axis].min(value);
+                max[axis] = max[axis].max(value);
+            }
+        }
+        let max_extent = (0..3).map(|axis| max[axis] - min[axis]).max().unwrap() as f64;
+        let cells_per_axis = (cities.len() as f64 / CITIES_PER_CELL).cbrt().max(1.0);
+        let cell_size = (max_extent / cells_per_axis).max(1.0);
+        let dims = [0, 1, 2].map(|axis| ((max[axis] - min[axis]) as f64 / cell_size) as usize + 1);
+        let mut grid = Self {
+            min,
+            cell_size,
+            dims,
+            cell_start: vec![0; dims[0] * dims[1] * dims[2] + 1],
+            cities: vec![0; cities.len()],
+        };
+        let cells = cities
+            .iter()
+            .map(|city| grid.cell_index(grid.cell(city)))
+            .collect::<Vec<_>>();
+        for &cell in &cells {
+            grid.cell_start[cell + 1] += 1;
+        }
+        for cell in 0..grid.cell_start.len() - 1 {
+            grid.cell_start[cell + 1] += grid.cell_start[cell];
+        }
+        let mut fill = grid.cell_start.clone();
+        for (city, &cell) in cells.iter().enumerate() {
+            grid.cities[fill[cell] as usize] = city as u32;
+            fill[cell] += 1;
+        }
+        grid
+    }
+
+    fn cell(&self, city: &City) -> [usize; 3] {
+        let offset = |value: u32, axis: usize| {
+            (((value - self.min[axis]) as f64 / self.cell_size) as usize).min(self.dims[axis] - 1)
+        };
+        [offset(city.x, 0), offset(city.y, 1), offset(city.z, 2)]
+    }
+
+    fn cell_index(&self, [x, y, z]: [usize; 3]) -> usize {
+        (x * self.dims[1] + y) * self.dims[2] + z
+    }
+
+    fn cell_cities(&self, cell: [usize; 3]) -> &[u32] {
+        let index = self.cell_index(cell);
+        &self.cities[self.cell_start[index] as usize..self.cell_start[index + 1] as usize]
+    }
+
+    /// Visits every cell whose Chebyshev distance from `center` is exactly `ring`.
+    /// Returns false once the ring lies completely outside the grid.
+    fn for_each_cell_in_ring(
+        &self,
+        center: [usize; 3],
+        ring: usize,
+        mut visit: impl FnMut([usize; 3]),
+    ) -> bool {
+        let range = |axis: usize| {
+            center[axis].saturating_sub(ring)..=(center[axis] + ring).min(self.dims[axis] - 1)
+        };
+        if (0..3).all(|axis| center[axis] < ring && center[axis] + ring >= self.dims[axis]) {
+            return false;
+        }
+        let on_ring = |axis: usize, value: usize| center[axis].abs_diff(value) == ring;
+        for x in range(0) {
+            for y in range(1) {
+                if on_ring(0, x) || on_ring(1, y) {
+                    for z in range(2) {
+                        visit([x, y, z]);
+                    }
+                } else {
+                    // only the two faces of the ring along z
+                    for z in [center[2].wrapping_sub(ring), center[2] + ring] {
+                        if z < self.dims[2] {
+                            visit([x, y, z]);
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+fn squared_distance(a: &City, b: &City) -> u64 {
+    let dx = a.x.abs_diff(b.x) as u64;
+    let dy = a.y.abs_diff(b.y) as u64;
+    let dz = a.z.abs_diff(b.z) as u64;
+    dx * dx + dy * dy + dz * dz
+}
+
+/// For every city, the indices of its `k` nearest other cities sorted by distance, ties
+/// broken by index. The lists are the candidate sets used by the constructions and local
+/// searches that scale to large n.
+///
+/// The cities are bucketed in a uniform grid of about `CITIES_PER_CELL` cities per cell
+/// and every query scans rings of cells around its own until no unseen cell can hold a
+/// closer city, which takes about O(n k) time on evenly spread instances.
+pub fn nearest_neighbor_lists(problem: &Problem, k: usize) -> Vec<Vec<u32>> {
+    let cities = &problem.cities;
+    let k = k.min(cities.len().saturating_sub(1));
+    if k == 0 {
+        return vec![Vec::new(); cities.len()];
+    }
+    let grid = Grid::new(cities);
+    let mut nearest = BinaryHeap::with_capacity(k + 1);
+    cities
+        .iter()
+        .enumerate()
+        .map(|(index, city)| {
+            let center = grid.cell(city);
+            let mut ring = 0;
+            loop {
+                let in_grid = grid.for_each_cell_in_ring(center, ring, |cell| {
+                    for &other in grid.cell_cities(cell) {
+                        if other as usize == index {
+                            continue;
+                        }
+                        let candidate = (squared_distance(city, &cities[other as usize]), other);
+                        if nearest.len() < k {
+                            nearest.push(candidate);
+                        } else if candidate < *nearest.peek().unwrap() {
+                            nearest.pop();
+                            nearest.push(candidate);
+                        }
+                    }
+                });
+                // every city of the next ring is at least `ring` cells away on some axis
+                let next_ring_distance = ring as f64 * grid.cell_size;
+                let done = nearest.len() == k
+                    && (nearest.peek().unwrap().0 as f64) < next_ring_distance.powi(2);
+                if done || !in_grid {
+                    break;
+                }
+                ring += 1;
+            }
+            let mut list = nearest.drain().collect::<Vec<_>>();
+            list.sort_unstable();
+            list.into_iter().map(|(_, other)| other).collect()
+        })
+        .collect()
+}
+
+/// The cities a tour construction has not visited yet, for finding the nearest of them
+/// once every candidate of the current city has been visited. Empty cells are skipped, so
+/// a query only pays for the cells around the answer.
+pub struct UnvisitedCities<'a> {
+    grid: &'a Grid,
+    cities: &'a [City],
+    is_visited: Vec<bool>,
+    /// How many unvisited cities every cell holds.
+    remaining: Vec<u32>,
+}
+
+impl<'a> UnvisitedCities<'a> {
+    /// Every city of `cities`, which `grid` must have been built from.
+    pub fn new(grid: &'a Grid, cities: &'a [City]) -> Self {
+        Self {
+            grid,
+            cities,
+            is_visited: vec![false; cities.len()],
+            remaining: grid.cell_start.windows(2).map(|w| w[1] - w[0]).collect(),
+        }
+    }
+
+    pub fn is_visited(&self, city: u32) -> bool {
+        self.is_visited[city as usize]
+    }
+
+    pub fn visit(&mut self, city: u32) {
+        if !self.is_visited[city as usize] {
+            self.is_visited[city as usize] = true;
+            let cell = self
+                .grid
+                .cell_index(self.grid.cell(&self.cities[city as usize]));
+            self.remaining[cell] -= 1;
+        }
+    }
+
+    /// The unvisited city nearest to `city`, ties broken by index.
+    pub fn nearest(&self, city: u32) -> Option<u32> {
+        let from = &self.cities[city as usize];
+        let center = self.grid.cell(from);
+        let mut nearest: Option<(u64, u32)> = None;
+        for ring in 0.. {
+            let in_grid = self.grid.for_each_cell_in_ring(center, ring, |cell| {
+                if self.remaining[self.grid.cell_index(cell)] == 0 {
+                    return;
+                }
+                for &other in self.grid.cell_cities(cell) {
+                    if self.is_visited[other as usize] {
+                        continue;
+                    }
+                    let candidate = (squared_distance(from, &self.cities[other as usize]), other);
+                    if nearest.is_none_or(|nearest| candidate < nearest) {
+                        nearest = Some(candidate);
+                    }
+                }
+            });
+            let next_ring_distance = ring as f64 * self.grid.cell_size;
+            let done =
+                nearest.is_some_and(|(distance, _)| (distance as f64) < next_ring_distance.powi(2));
+            if done || !in_grid {
+                break;
+            }
+        }
+        nearest.map(|(_, other)| other)
+    }
+}
+
+/// The length of the edge between two cities of `problem`.
+pub fn edge_length(problem: &Problem, a: u32, b: u32) -> f32 {
+    problem.cities[a as usize].distance(&problem.cities[b as usize])
+}
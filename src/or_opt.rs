@@ -0,0 +1,135 @@
+use std::time::Instant;
+
+use crate::{
+    local_search::{ActiveCities, LocalSearch, deadline_passed, distance},
+    problem::{Problem, Solution},
+    tour::ArrayTour,
+};
+
+/// The longest segment that Or-opt moves.
+pub const OR_OPT_MAX_SEGMENT_LENGTH: usize = 3;
+
+/// Moves the segment `first..last`, which runs in direction `forward`, between the
+/// adjacent cities `c` and `e` elsewhere in the tour, with its end `end` next to `c`.
+pub(crate) fn move_segment(
+    tour: &mut ArrayTour,
+    (first, last): (u32, u32),
+    forward: bool,
+    end: u32,
+    (c, e): (u32, u32),
+) {
+    let before = tour.succ(first, !forward);
+    let after = tour.succ(last, forward);
+    // the edge as seen in direction `forward`, on the path from `after` to `before`
+    let (e1, e2) = if tour.succ(c, forward) == e {
+        (c, e)
+    } else {
+        (e, c)
+    };
+    // whether the segment keeps its direction between e1 and e2
+    let keeps_direction = (e1 == c) == (end == first);
+    let x = (first, last);
+    if tour.path_length(after, e1, forward) <= tour.path_length(e2, before, forward) {
+        // before [segment] [after..e1] e2 -> before [after..e1] [segment] e2
+        tour.swap_segments(before, x, (after, e1), e2, !keeps_direction, false);
+    } else {
+        // e1 [e2..before] [segment] after -> e1 [segment] [e2..before] after
+        tour.swap_segments(e1, (e2, before), x, after, false, !keeps_direction);
+    }
+}
+
+/// Tries to move the segment of `length` cities that starts at `first` and runs in
+/// direction `forward` between two adjacent cities elsewhere in the tour, in either
+/// orientation. One end of the segment must become the neighbor of one of its candidates.
+/// Applies the first improving move and returns its gain and the cities whose edges
+/// changed.
+fn improve_segment(
+    tour: &mut ArrayTour,
+    problem: &Problem,
+    neighbor_lists: &[Vec<u32>],
+    first: u32,
+    length: usize,
+    forward: bool,
+) -> Option<(f64, [u32; 6])> {
+    let mut last = first;
+    for _ in 1..length {
+        last = tour.succ(last, forward);
+    }
+    let before = tour.succ(first, !forward);
+    let after = tour.succ(last, forward);
+    let removal_gain = distance(problem, before, first) + distance(problem, last, after)
+        - distance(problem, before, after);
+    if removal_gain <= 0.0 {
+        return None;
+    }
+    let in_segment = |city: u32| tour.between(first, city, last, forward);
+    for (end, other_end) in [(first, last), (last, first)] {
+        for &c in &neighbor_lists[end as usize] {
+            let cs = distance(problem, c, end);
+            if cs >= removal_gain {
+                break;
+            }
+            if in_segment(c) {
+                continue;
+            }
+            for e in [tour.next(c), tour.prev(c)] {
+                if in_segment(e) {
+                    continue;
+                }
+                let gain =
+                    removal_gain + distance(problem, c, e) - cs - distance(problem, other_end, e);
+                if gain <= 0.0 {
+                    continue;
+                }
+                move_segment(tour, (first, last), forward, end, (c, e));
+                return Some((gain, [first, last, before, after, c, e]));
+            }
+        }
+    }
+    None
+}
+
+/// Or-opt with candidate lists and don't-look bits: moves segments of 1 to
+/// `OR_OPT_MAX_SEGMENT_LENGTH` cities between two other adjacent cities, in either
+/// orientation, when that shortens the tour. Every segment that starts or ends at an
+/// active city is tried. Stops at a local optimum or when `deadline` passes, and returns
+/// the total gain.
+pub fn or_opt(
+    tour: &mut ArrayTour,
+    problem: &Problem,
+    neighbor_lists: &[Vec<u32>],
+    active: &mut ActiveCities,
+    deadline: Option<Instant>,
+) -> f64 {
+    let mut total_gain = 0.0;
+    'cities: while let Some(city) = active.pop() {
+        if deadline_passed(deadline) {
+            break;
+        }
+        for length in 1..=OR_OPT_MAX_SEGMENT_LENGTH {
+            // the segment plus at least three other cities
+            if length + 3 > tour.len() {
+                break;
+            }
+            for forward in [true, false] {
+                let improvement =
+                    improve_segment(tour, problem, neighbor_lists, city, length, forward);
+                if let Some((gain, changed)) = improvement {
+                    total_gain += gain;
+                    for city in changed {
+                        active.push(city);
+                    }
+                    continue 'cities;
+                }
+            }
+        }
+    }
+    total_gain
+}
+
+impl Solution {
+    /// The tour improved by [`or_opt`] until no segment move helps or `deadline` passes.
+    pub fn or_opt(&self, neighbor_lists: &[Vec<u32>], deadline: Option<Instant>) -> Solution {
+        self.improve_with(&[LocalSearch::OrOpt], neighbor_lists, deadline)
+    }
+}
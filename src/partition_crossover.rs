@@ -0,0 +1,175 @@
+use crate::{local_search::distance, problem::Solution};
+
+/// Marks a city that lies in no partition component.
+const NONE: u32 = u32::MAX;
+
+/// The two neighbors of every city in a tour.
+fn adjacency(order: &[u32]) -> Vec<[u32; 2]> {
+    let n = order.len();
+    let mut adjacency = vec![[0; 2]; n];
+    for (i, &city) in order.iter().enumerate() {
+        adjacency[city as usize] = [order[(i + n - 1) % n], order[(i + 1) % n]];
+    }
+    adjacency
+}
+
+/// Generalized Partition Crossover (GPX). Removing the edges both parents share splits the
+/// union of their edges into partition components. A component is feasible when the
+/// paths of both parents inside it join its portals, the cities with a shared edge
+/// leaving it, in the same pairs, so either parent's paths can be used there. Paths of
+/// shared edges that leave a component and return to it count as inside it. Starting
+/// from each parent in turn, every feasible component takes the shorter of the two
+/// parents' paths, which gives the best of the 2^k children in linear time; the other
+/// components stay with the starting parent. Calls `callback` with every child that
+/// differs from its starting parent, until it returns false.
+pub fn partition_crossover(
+    parent1: &Solution,
+    parent2: &Solution,
+    callback: &mut dyn FnMut(&Solution) -> bool,
+) {
+    let problem = parent1.problem.upgrade().unwrap();
+    let n = parent1.order_without_loop.len();
+    let adjacencies = [
+        adjacency(&parent1.order_without_loop),
+        adjacency(&parent2.order_without_loop),
+    ];
+    let is_shared = |city: usize, other: u32| {
+        adjacencies[0][city].contains(&other) && adjacencies[1][city].contains(&other)
+    };
+    // the partition components, connected by the edges that only one parent has
+    let mut component_of = vec![NONE; n];
+    let mut components: Vec<Vec<u32>> = Vec::new();
+    for first in 0..n {
+        if component_of[first] != NONE
+            || adjacencies[0][first]
+                .iter()
+                .all(|&other| is_shared(first, other))
+        {
+            continue;
+        }
+        let label = components.len() as u32;
+        let mut component = vec![first as u32];
+        component_of[first] = label;
+        let mut next = 0;
+        while let Some(&city) = component.get(next) {
+            next += 1;
+            for adjacency in &adjacencies {
+                for &other in &adjacency[city as usize] {
+                    if component_of[other as usize] == NONE && !is_shared(city as usize, other) {
+                        component_of[other as usize] = label;
+                        component.push(other);
+                    }
+                }
+            }
+        }
+        components.push(component);
+    }
+    // the city where the path of shared edges that starts with `(city, next)` ends in a
+    // component, with the city before it
+    let shared_path_end = |city: u32, next: u32| {
+        let (mut prev, mut city) = (city, next);
+        while component_of[city as usize] == NONE {
+            let [a, b] = adjacencies[0][city as usize];
+            (prev, city) = (city, if a == prev { b } else { a });
+        }
+        (city, prev)
+    };
+    // every feasible component, with how much longer the second parent's paths in it are,
+    // counted from both ends of every edge
+    let mut feasible = Vec::new();
+    for (label, component) in components.iter().enumerate() {
+        let inside = |city: u32| component_of[city as usize] == label as u32;
+        // a shared path that leaves the component and returns to it counts as inside, so
+        // the portals are the cities whose shared path leads to another component
+        let portals = component
+            .iter()
+            .copied()
+            .filter(|&city| {
+                adjacencies[0][city as usize]
+                    .iter()
+                    .any(|&other| !inside(other) && !inside(shared_path_end(city, other).0))
+            })
+            .collect::<Vec<_>>();
+        if portals.is_empty() {
+            continue;
+        }
+        let mut pairs = [Vec::new(), Vec::new()];
+        for (adjacency, pairs) in adjacencies.iter().zip(&mut pairs) {
+            for &portal in &portals {
+                // a portal has one edge inside the component and a shared path leaving it
+                let [a, b] = adjacency[portal as usize];
+                let leaves =
+                    |other: u32| !inside(other) && !inside(shared_path_end(portal, other).0);
+                let (mut prev, mut city) = (portal, if leaves(a) { b } else { a });
+                loop {
+                    let [a, b] = adjacency[city as usize];
+                    let next = if a == prev { b } else { a };
+                    if inside(next) {
+                        (prev, city) = (city, next);
+                        continue;
+                    }
+                    let (end, before_end) = shared_path_end(city, next);
+                    if !inside(end) {
+                        break;
+                    }
+                    (prev, city) = (before_end, end);
+                }
+                pairs.push((portal.min(city), portal.max(city)));
+            }
+            pairs.sort_unstable();
+        }
+        if pairs[0] != pairs[1] {
+            continue;
+        }
+        let [length1, length2] = [0, 1].map(|parent| {
+            component
+                .iter()
+                .map(|&city| {
+                    adjacencies[parent][city as usize]
+                        .iter()
+                        .map(|&other| distance(&problem, city, other))
+                        .sum::<f64>()
+                })
+                .sum::<f64>()
+        });
+        feasible.push((label, length2 - length1));
+    }
+    // with every component feasible, both starting parents give the same child
+    let bases = if feasible.len() == components.len() {
+        1
+    } else {
+        2
+    };
+    for base in 0..bases {
+        let mut child_adjacency = adjacencies[base].clone();
+        let mut changed = false;
+        for &(label, difference) in &feasible {
+            let other = 1 - base;
+            let other_is_shorter = if base == 0 {
+                difference < 0.0
+            } else {
+                difference > 0.0
+            };
+            if other_is_shorter {
+                changed = true;
+                for &city in &components[label] {
+                    child_adjacency[city as usize] = adjacencies[other][city as usize];
+                }
+            }
+        }
+        if !changed {
+            continue;
+        }
+        let mut order = Vec::with_capacity(n);
+        let (mut prev, mut city) = (child_adjacency[0][0], 0);
+        while order.len() < n {
+            order.push(city);
+            let [a, b] = child_adjacency[city as usize];
+            (prev, city) = (city, if a == prev { b } else { a });
+        }
+        let child = Solution::new(order, parent1.problem.clone(), None);
+        if !callback(&child) {
+            return;
+        }
+    }
+}
@@ -2,20 +2,19 @@ use core::f32;
 use std::{
     cell::RefCell,
     cmp::Ordering,
-    collections::BinaryHeap,
     rc::{Rc, Weak},
+    time::Instant,
 };
 
 use itertools::Itertools;
-use ordered_float::NotNan;
-use rand::{
-    Rng,
-    distr::{Distribution, weighted::WeightedIndex},
-    rngs::StdRng,
-    seq::SliceRandom,
-};
+use rand::{Rng, rngs::StdRng};
 
-use crate::cover_tree::{CoverTree, Distance};
+use crate::{
+    cover_tree::Distance,
+    neighbors::{Grid, UnvisitedCities, edge_length},
+    replacement::{Offspring, ReplacementStrategy},
+    selection::{ParentSampler, RouletteSelection, SelectionStrategy},
+};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct City {
@@ -71,9 +70,10 @@ impl<T> Ord for RcKey<T> {
 
 impl Distance for City {
     fn distance(&self, other: &Self) -> f32 {
-        let dx = self.x as i32 - other.x as i32;
-        let dy = self.y as i32 - other.y as i32;
-        let dz = self.z as i32 - other.z as i32;
+        // squared in 64 bits, since differences above 46340 overflow 32 bits
+        let dx = self.x.abs_diff(other.x) as u64;
+        let dy = self.y.abs_diff(other.y) as u64;
+        let dz = self.z.abs_diff(other.z) as u64;
         ((dx * dx + dy * dy + dz * dz) as f32).sqrt()
     }
 }
@@ -143,15 +143,17 @@ impl Solution {
         if let Some(length) = total_length.as_ref() {
             return *length;
         }
-        let mut new_total_length = 0.0;
+        // accumulate in f64, an f32 sum drifts noticeably on tours with a million edges
+        let mut new_total_length = 0.0f64;
         let problem = self.problem.upgrade().expect("Problem has been dropped");
         for i in 0..self.order_without_loop.len() {
             let city_a = &problem.cities[self.order_without_loop[i] as usize];
             let city_b = &problem.cities
                 [self.order_without_loop[(i + 1) % self.order_without_loop.len()] as usize];
             let dist = city_a.distance(city_b);
-            new_total_length += dist;
+            new_total_length += dist as f64;
         }
+        let new_total_length = new_total_length as f32;
         *total_length = Some(new_total_length);
         new_total_length
     }
@@ -169,81 +171,25 @@ impl Solution {
     //     Self::new(order, Rc::downgrade(problem), None)
     // }
 
+    /// Always moves on to the nearest unvisited city, found in a grid so that duplicate
+    /// cities are fine.
     pub fn from_nearest_neighbor(problem: &Rc<Problem>, start_index: usize) -> Self {
         assert!(start_index < problem.cities.len());
-        let mut cover_tree = CoverTree::new();
-        let offset = start_index;
-        let initial_city = problem.cities[offset];
-        let mut current_city = initial_city;
+        let grid = Grid::new(&problem.cities);
+        let mut unvisited = UnvisitedCities::new(&grid, &problem.cities);
+        let mut current = start_index as u32;
         let mut total_distance = 0.0;
-        let mut ordered_cities = vec![offset as u32];
-        // populate the cover tree
-        for (i, city) in problem.cities.iter().enumerate() {
-            cover_tree.insert(*city, i as u32);
-        }
-        cover_tree.remove(&initial_city);
-        for _ in 0..problem.cities.len() - 1 {
-            let (nearest_city, index, distance) =
-                cover_tree.nearest_neighbor(&current_city).unwrap();
-            // println!("Current city: {:?}, Nearest city: {:?}, Dist: {}", current_city, nearest_city, dist);
-            // current_city = nearest_city;
-            total_distance += distance;
-            cover_tree.remove(&nearest_city);
-            ordered_cities.push(index);
-            current_city = nearest_city;
+        let mut ordered_cities = vec![current];
+        unvisited.visit(current);
+        while let Some(nearest) = unvisited.nearest(current) {
+            total_distance += edge_length(problem, current, nearest);
+            unvisited.visit(nearest);
+            ordered_cities.push(nearest);
+            current = nearest;
         }
-        assert!(cover_tree.is_empty());
-        // ordered_cities.push(0);
-        total_distance += current_city.distance(&initial_city);
+        total_distance += edge_length(problem, current, start_index as u32);
         Self::new(ordered_cities, Rc::downgrade(problem), Some(total_distance))
     }
-    // pub fn targeted_two_opt(&self) -> Self {
-    //     let problem = self.problem.upgrade().expect("Problem has been dropped");
-    //     let mut best_order = self.order_without_loop.clone();
-    //     // let mut best_distance = self.total_distance();
-
-    //     let n = best_order.len();
-    //     if n < 4 {
-    //         panic!("Solution too short for 2-opt optimization");
-    //     }
-
-    //     let cities = &problem.cities;
-
-    //     // let dist = |a: u32, b: u32| cities[a as usize].distance(&cities[b as usize]);
-    //     loop {
-    //         let mut found_shorter = false;
-    //         let start = best_order[0];
-    //         let end = best_order[n - 1];
-    //         for i in 1..n - 2 {
-    //             let a = best_order[i];
-    //             let b = best_order[i + 1];
-    //             let city_a = &cities[a as usize];
-    //             let city_b = &cities[b as usize];
-    //             let city_start = &cities[start as usize];
-    //             let city_end = &cities[end as usize];
-    //             let current_cost = city_a.distance(city_b) + city_end.distance(city_start);
-    //             let new_cost = city_a.distance(city_end) + city_b.distance(city_start);
-    //             if new_cost < current_cost {
-    //                 best_order[0..=i].reverse();
-    //                 let new_solution =
-    //                     Solution::new(best_order.clone(), Rc::downgrade(&problem), None);
-    //                 let new_distance = new_solution.total_distance();
-    //                 assert!(self.total_distance() - current_cost == new_distance - new_cost);
-    //                 found_shorter = true;
-    //                 println!("found shorter");
-    //                 break;
-    //             } else {
-    //                 println!("not shorter");
-    //             }
-    //         }
-    //         if !found_shorter {
-    //             println!("did not find shorter");
-    //             break;
-    //         }
-    //     }
-    //     Solution::new(best_order, Rc::downgrade(&problem), None)
-    // }
-
     pub fn from_brute_force(problem: &Rc<Problem>) -> Self {
         let cities = &problem.cities;
         if cities.len() <= 1 {
@@ -279,68 +225,31 @@ impl Solution {
                 best_order = candidate;
             }
         }
-        Solution::new(best_order, Rc::downgrade(&problem), Some(best_distance))
+        Solution::new(best_order, Rc::downgrade(problem), Some(best_distance))
     }
 }
 
 pub struct Population {
     pub solutions: Vec<Rc<Solution>>,
-    roulette: RefCell<Option<WeightedIndex<f32>>>,
-    max_length: RefCell<Option<f32>>,
-    min_length: RefCell<Option<f32>>,
+    selection: Rc<dyn SelectionStrategy>,
+    sampler: RefCell<Option<Box<dyn ParentSampler>>>,
 }
 
 impl Population {
+    /// A population whose parents are picked by [`RouletteSelection`].
     pub fn new(solutions: Vec<Rc<Solution>>) -> Self {
+        Self::with_selection(solutions, Rc::new(RouletteSelection))
+    }
+    pub fn with_selection(
+        solutions: Vec<Rc<Solution>>,
+        selection: Rc<dyn SelectionStrategy>,
+    ) -> Self {
         Self {
             solutions,
-            max_length: RefCell::new(None),
-            min_length: RefCell::new(None),
-            roulette: RefCell::new(None),
+            selection,
+            sampler: RefCell::new(None),
         }
     }
-    fn get_min_max(&self) -> (f32, f32) {
-        let mut min_length = f32::MAX;
-        let mut max_length = f32::MIN;
-        for solution in &self.solutions {
-            let length = solution.total_distance();
-            if length < min_length {
-                min_length = length;
-            }
-            if length > max_length {
-                max_length = length;
-            }
-        }
-        (min_length, max_length)
-    }
-    fn calculate_fitness(total_length: f32, min_length: f32, max_length: f32) -> f32 {
-        let epsilon: f32 = 1e-6;
-        (max_length - total_length + epsilon) / (max_length - min_length + epsilon)
-    }
-    fn calculate_roulette(&self) -> WeightedIndex<f32> {
-        let mut min_length = self.min_length.borrow_mut();
-        let mut max_length = self.max_length.borrow_mut();
-        if min_length.is_none() || max_length.is_none() {
-            let (new_min_length, new_max_length) = self.get_min_max();
-            *min_length = Some(new_min_length);
-            *max_length = Some(new_max_length);
-        }
-        let min_length = min_length.unwrap();
-        let max_length = max_length.unwrap();
-        // println!("Min length: {}, Max length: {}", min_length, max_length);
-        // calculate unnormalized fitnesses
-        let fitnesses = self
-            .solutions
-            .iter()
-            .map(|solution| {
-                let length = solution.total_distance();
-                Self::calculate_fitness(length, min_length, max_length)
-            })
-            .collect::<Vec<_>>();
-        // weighted index will normalize the fitnesses for us
-        // println!("Fitnesses: {:?}", fitnesses);
-        WeightedIndex::new(fitnesses).expect("Failed to create WeightedIndex")
-    }
     // pub fn from_random_shuffle(problem: &Rc<Problem>, size: usize, rng: &mut impl Rng) -> Self {
     //     let solutions = (0..size)
     //         .map(|_| {
@@ -351,41 +260,40 @@ impl Population {
     //     Population::new(solutions)
     // }
     pub fn sample_parent(&self, rng: &mut impl Rng) -> Rc<Solution> {
-        let mut roulette = self.roulette.borrow_mut();
-        let roulette = roulette.get_or_insert_with(|| self.calculate_roulette());
-        let index = roulette.sample(rng);
+        let mut sampler = self.sampler.borrow_mut();
+        let sampler = sampler.get_or_insert_with(|| {
+            let lengths = self
+                .solutions
+                .iter()
+                .map(|solution| solution.total_distance())
+                .collect::<Vec<_>>();
+            self.selection.sampler(&lengths)
+        });
+        let index = sampler.sample(rng);
         self.solutions[index].clone()
     }
-    pub fn shrink_population(&mut self, target_size: usize, rng: &mut StdRng) -> Vec<Rc<Solution>> {
-        if self.solutions.len() <= target_size {
+    /// The `target_size` tours of this population and its `offspring` that survive
+    /// into the next generation, as picked by `replacement` within `deadline`.
+    pub fn shrink_population(
+        &mut self,
+        offspring: Vec<Offspring>,
+        target_size: usize,
+        replacement: &dyn ReplacementStrategy,
+        deadline: Option<Instant>,
+        rng: &mut StdRng,
+    ) -> Vec<Rc<Solution>> {
+        // the sampler refers to the tours by their index
+        self.sampler.borrow_mut().take();
+        let population = std::mem::take(&mut self.solutions);
+        if population.len() + offspring.len() <= target_size {
             // No need to shrink
-            return self.solutions.clone();
-        }
-        // reset roulette and min/max lengths
-        self.roulette.borrow_mut().take();
-        self.min_length.borrow_mut().take();
-        self.max_length.borrow_mut().take();
-
-        let half_target_size = target_size / 2;
-        let (first_half, _, _) = self
-            .solutions
-            .select_nth_unstable_by_key(half_target_size, |solution| {
-                NotNan::new(solution.total_distance()).unwrap()
-            });
-        assert!(first_half.len() == half_target_size);
-        let mut shrunk_population = first_half.to_vec();
-        let mut last_half = self.solutions[half_target_size..]
-            .iter()
-            .cloned()
-            .collect::<Vec<_>>();
-        let target_last_half_size = target_size - half_target_size;
-        for _ in 0..target_last_half_size {
-            let random_index = rng.random_range(0..last_half.len());
-            let solution = last_half.swap_remove(random_index);
-            shrunk_population.push(solution);
+            let mut solutions = population;
+            solutions.extend(offspring.into_iter().map(|offspring| offspring.solution));
+            return solutions;
         }
-        assert!(shrunk_population.len() == target_size);
-        shrunk_population
+        let survivors = replacement.survivors(population, offspring, target_size, deadline, rng);
+        assert!(survivors.len() == target_size);
+        survivors
     }
     // pub fn best_solution(&self) -> Rc<Solution> {
     //     self.solutions
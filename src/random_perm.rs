@@ -59,7 +59,7 @@ impl Feistel {
         }
     }
 
-    fn feistel(&self, mut x: u32) -> u32 {
+    fn feistel(&self, x: u32) -> u32 {
         let half_bits = self.bits / 2;
         let l_mask = (1u32 << half_bits) - 1;
 
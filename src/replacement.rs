@@ -0,0 +1,235 @@
+use std::{collections::HashMap, rc::Rc, time::Instant};
+
+use ordered_float::NotNan;
+use rand::{Rng, rngs::StdRng};
+
+use crate::{local_search::deadline_passed, problem::Solution};
+
+/// A child of the genetic algorithm with the parents it was built from.
+pub struct Offspring {
+    pub solution: Rc<Solution>,
+    pub parents: [Rc<Solution>; 2],
+}
+
+/// How the genetic algorithm picks the tours that survive into the next generation.
+pub trait ReplacementStrategy {
+    /// Picks `target_size` survivors among the current population and its offspring,
+    /// which together have more tours than that. A strategy that compares tours with
+    /// each other settles for a cheaper choice once `deadline` has passed.
+    fn survivors(
+        &self,
+        population: Vec<Rc<Solution>>,
+        offspring: Vec<Offspring>,
+        target_size: usize,
+        deadline: Option<Instant>,
+        rng: &mut StdRng,
+    ) -> Vec<Rc<Solution>>;
+
+    /// How the strategy is called in progress messages.
+    fn name(&self) -> &'static str;
+}
+
+/// (μ+λ) truncation: the shortest tours of the population and the offspring survive.
+pub struct TruncationReplacement;
+
+/// The `elite_count` shortest tours survive, half of the target size by default, and the
+/// others are drawn uniformly at random from the rest.
+pub struct ElitistReplacement {
+    pub elite_count: Option<usize>,
+}
+
+/// Deterministic crowding: every child takes the place of the parent that shares the
+/// most edges with it, when it is shorter than the tour in that place. A population
+/// larger than the target size keeps its shortest tours, and a smaller one is filled up
+/// with the shortest children that found no place.
+pub struct CrowdingReplacement;
+
+/// Diversity-preserving replacement: from the shortest tour on, a tour survives only
+/// when its edge distance to the last [`DIVERSITY_COMPARISONS`] survivors is at least
+/// `min_distance` times the number of cities. Shorter tours fill the places that are
+/// still free after that, or once the deadline has passed.
+pub struct DiversityReplacement {
+    pub min_distance: f64,
+}
+
+/// How many survivors [`DiversityReplacement`] compares a tour with. The last survivors
+/// are the closest to it in length, where copies of it are found, and comparing with no
+/// more of them keeps the replacement linear in the size of the population.
+pub const DIVERSITY_COMPARISONS: usize = 32;
+
+/// The two neighbors of every city in a tour.
+fn adjacency(order: &[u32]) -> Vec<[u32; 2]> {
+    let n = order.len();
+    let mut adjacency = vec![[0; 2]; n];
+    for (i, &city) in order.iter().enumerate() {
+        adjacency[city as usize] = [order[(i + n - 1) % n], order[(i + 1) % n]];
+    }
+    adjacency
+}
+
+/// How many edges of `order` are missing from the tour with the neighbors `adjacency`.
+fn missing_edges(adjacency: &[[u32; 2]], order: &[u32]) -> usize {
+    let n = order.len();
+    (0..n)
+        .filter(|&i| !adjacency[order[i] as usize].contains(&order[(i + 1) % n]))
+        .count()
+}
+
+/// Whether at least `count` edges of `order` are missing from the tour with the neighbors
+/// `adjacency`, which stops looking once it has found them.
+fn misses_at_least(adjacency: &[[u32; 2]], order: &[u32], count: usize) -> bool {
+    let n = order.len();
+    count == 0
+        || (0..n)
+            .filter(|&i| !adjacency[order[i] as usize].contains(&order[(i + 1) % n]))
+            .nth(count - 1)
+            .is_some()
+}
+
+/// The number of edges of `a` that `b` does not have, which is symmetric.
+pub fn edge_distance(a: &Solution, b: &Solution) -> usize {
+    missing_edges(&adjacency(&a.order_without_loop), &b.order_without_loop)
+}
+
+fn length_key(solution: &Rc<Solution>) -> NotNan<f32> {
+    NotNan::new(solution.total_distance()).unwrap()
+}
+
+impl ReplacementStrategy for TruncationReplacement {
+    fn survivors(
+        &self,
+        mut population: Vec<Rc<Solution>>,
+        offspring: Vec<Offspring>,
+        target_size: usize,
+        _deadline: Option<Instant>,
+        _rng: &mut StdRng,
+    ) -> Vec<Rc<Solution>> {
+        population.extend(offspring.into_iter().map(|offspring| offspring.solution));
+        population.select_nth_unstable_by_key(target_size, length_key);
+        population.truncate(target_size);
+        population
+    }
+
+    fn name(&self) -> &'static str {
+        "truncation"
+    }
+}
+
+impl ReplacementStrategy for ElitistReplacement {
+    fn survivors(
+        &self,
+        mut population: Vec<Rc<Solution>>,
+        offspring: Vec<Offspring>,
+        target_size: usize,
+        _deadline: Option<Instant>,
+        rng: &mut StdRng,
+    ) -> Vec<Rc<Solution>> {
+        population.extend(offspring.into_iter().map(|offspring| offspring.solution));
+        let elite_count = self.elite_count.unwrap_or(target_size / 2).min(target_size);
+        let (elite, _, _) = population.select_nth_unstable_by_key(elite_count, length_key);
+        assert!(elite.len() == elite_count);
+        let mut survivors = elite.to_vec();
+        let mut rest = population[elite_count..].to_vec();
+        for _ in 0..target_size - elite_count {
+            let random_index = rng.random_range(0..rest.len());
+            survivors.push(rest.swap_remove(random_index));
+        }
+        survivors
+    }
+
+    fn name(&self) -> &'static str {
+        "elitist"
+    }
+}
+
+impl ReplacementStrategy for CrowdingReplacement {
+    fn survivors(
+        &self,
+        mut population: Vec<Rc<Solution>>,
+        offspring: Vec<Offspring>,
+        target_size: usize,
+        _deadline: Option<Instant>,
+        _rng: &mut StdRng,
+    ) -> Vec<Rc<Solution>> {
+        let place_of = population
+            .iter()
+            .enumerate()
+            .map(|(place, solution)| (Rc::as_ptr(solution), place))
+            .collect::<HashMap<_, _>>();
+        let mut homeless = Vec::new();
+        for Offspring { solution, parents } in offspring {
+            let child_adjacency = adjacency(&solution.order_without_loop);
+            // a parent whose place an earlier child took is compared through that child
+            let place = parents
+                .iter()
+                .filter_map(|parent| place_of.get(&Rc::as_ptr(parent)).copied())
+                .min_by_key(|&place| {
+                    missing_edges(&child_adjacency, &population[place].order_without_loop)
+                });
+            match place {
+                Some(place) if solution.total_distance() < population[place].total_distance() => {
+                    population[place] = solution;
+                }
+                _ => homeless.push(solution),
+            }
+        }
+        if population.len() > target_size {
+            population.select_nth_unstable_by_key(target_size, length_key);
+            population.truncate(target_size);
+        } else {
+            homeless.sort_unstable_by_key(length_key);
+            population.extend(homeless.into_iter().take(target_size - population.len()));
+        }
+        population
+    }
+
+    fn name(&self) -> &'static str {
+        "crowding"
+    }
+}
+
+impl ReplacementStrategy for DiversityReplacement {
+    fn survivors(
+        &self,
+        mut population: Vec<Rc<Solution>>,
+        offspring: Vec<Offspring>,
+        target_size: usize,
+        deadline: Option<Instant>,
+        _rng: &mut StdRng,
+    ) -> Vec<Rc<Solution>> {
+        population.extend(offspring.into_iter().map(|offspring| offspring.solution));
+        population.sort_unstable_by_key(length_key);
+        let num_cities = population[0].order_without_loop.len();
+        let min_distance = (self.min_distance * num_cities as f64).ceil() as usize;
+        let mut survivors: Vec<Rc<Solution>> = Vec::with_capacity(target_size);
+        let mut survivor_adjacencies = Vec::with_capacity(target_size);
+        let mut too_close = Vec::new();
+        let mut candidates = population.into_iter();
+        while survivors.len() < target_size && !deadline_passed(deadline) {
+            let Some(solution) = candidates.next() else {
+                break;
+            };
+            let is_distant = survivor_adjacencies
+                .iter()
+                .rev()
+                .take(DIVERSITY_COMPARISONS)
+                .all(|adjacency: &Vec<[u32; 2]>| {
+                    misses_at_least(adjacency, &solution.order_without_loop, min_distance)
+                });
+            if is_distant {
+                survivor_adjacencies.push(adjacency(&solution.order_without_loop));
+                survivors.push(solution);
+            } else {
+                too_close.push(solution);
+            }
+        }
+        // the tours that were too close are all shorter than the ones not looked at yet
+        let free = target_size - survivors.len();
+        survivors.extend(too_close.into_iter().chain(candidates).take(free));
+        survivors
+    }
+
+    fn name(&self) -> &'static str {
+        "diversity"
+    }
+}
@@ -0,0 +1,92 @@
+use std::{cmp::Reverse, rc::Rc};
+
+use ordered_float::NotNan;
+
+use crate::{
+    greedy_edge::{Fragments, NO_CITY, join_fragments},
+    neighbors::{edge_length, nearest_neighbor_lists},
+    problem::{Problem, Solution},
+};
+
+/// How many nearest neighbors of every city are considered as savings pairs.
+pub const SAVINGS_NEIGHBORS: usize = 10;
+
+/// The city nearest to the centroid of all cities.
+fn central_city(problem: &Problem) -> u32 {
+    let num_cities = problem.cities.len() as f64;
+    let mut centroid = [0.0f64; 3];
+    for city in &problem.cities {
+        centroid[0] += city.x as f64 / num_cities;
+        centroid[1] += city.y as f64 / num_cities;
+        centroid[2] += city.z as f64 / num_cities;
+    }
+    let squared_distance = |index: usize| {
+        let city = problem.cities[index];
+        let dx = city.x as f64 - centroid[0];
+        let dy = city.y as f64 - centroid[1];
+        let dz = city.z as f64 - centroid[2];
+        NotNan::new(dx * dx + dy * dy + dz * dz).unwrap()
+    };
+    (0..problem.cities.len())
+        .min_by_key(|&index| squared_distance(index))
+        .unwrap() as u32
+}
+
+impl Solution {
+    /// The Clarke-Wright savings construction. Every city starts on its own route out of
+    /// a hub, the city nearest to the centroid, and routes are merged end to end by the
+    /// largest saving `d(hub, i) + d(hub, j) - d(i, j)`. Pairs come from the
+    /// `SAVINGS_NEIGHBORS` nearest neighbors of every city; the routes left when they run
+    /// out are chained nearest end first, starting from the hub. The tour is rotated to
+    /// start at `start_index`.
+    pub fn from_savings(problem: &Rc<Problem>, start_index: usize) -> Self {
+        let num_cities = problem.cities.len();
+        assert!(start_index < num_cities);
+        let hub = central_city(problem);
+        let neighbor_lists = nearest_neighbor_lists(problem, SAVINGS_NEIGHBORS);
+        let mut savings = Vec::new();
+        for (a, neighbors) in neighbor_lists.iter().enumerate() {
+            let a = a as u32;
+            for &b in neighbors {
+                if a == hub || b == hub {
+                    continue;
+                }
+                // the same pair shows up in both lists when the cities are mutual neighbors
+                if a < b || !neighbor_lists[b as usize].contains(&a) {
+                    let saving = edge_length(problem, hub, a) + edge_length(problem, hub, b)
+                        - edge_length(problem, a, b);
+                    savings.push((Reverse(NotNan::new(saving).unwrap()), a.min(b), a.max(b)));
+                }
+            }
+        }
+        savings.sort_unstable();
+
+        // the route neighbors of every city other than the hub, NO_CITY while missing
+        let mut adjacent = vec![[NO_CITY; 2]; num_cities];
+        let mut degree = vec![0u8; num_cities];
+        let mut routes = Fragments::new(num_cities);
+        for (_, a, b) in savings {
+            // only the ends of two different routes can be merged
+            if degree[a as usize] == 2 || degree[b as usize] == 2 {
+                continue;
+            }
+            if routes.find(a) == routes.find(b) {
+                continue;
+            }
+            routes.union(a, b);
+            adjacent[a as usize][degree[a as usize] as usize] = b;
+            adjacent[b as usize][degree[b as usize] as usize] = a;
+            degree[a as usize] += 1;
+            degree[b as usize] += 1;
+        }
+
+        let mut order = join_fragments(problem, &adjacent, hub);
+        assert_eq!(order.len(), num_cities);
+        let start_position = order
+            .iter()
+            .position(|&city| city == start_index as u32)
+            .unwrap();
+        order.rotate_left(start_position);
+        Solution::new(order, Rc::downgrade(problem), None)
+    }
+}
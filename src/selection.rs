@@ -0,0 +1,182 @@
+use rand::{
+    Rng, RngCore,
+    distr::{Distribution, weighted::WeightedIndex},
+    seq::{SliceRandom, index},
+};
+
+/// How the genetic algorithm picks parents from its population. A strategy prepares a
+/// [`ParentSampler`] for every population, since most of them sort or weigh the tours
+/// once per generation.
+pub trait SelectionStrategy {
+    /// Prepares sampling from a population whose tours have the given lengths.
+    fn sampler(&self, lengths: &[f32]) -> Box<dyn ParentSampler>;
+
+    /// How the strategy is called in progress messages.
+    fn name(&self) -> &'static str;
+}
+
+/// Samples parents from one population.
+pub trait ParentSampler {
+    /// The index of the next parent.
+    fn sample(&mut self, rng: &mut dyn RngCore) -> usize;
+}
+
+/// Fitness-proportional roulette selection, where the fitness of a tour is how much
+/// shorter it is than the longest one, normalized by the spread of the lengths.
+pub struct RouletteSelection;
+
+/// Tournament selection: the shortest of `size` distinct tours drawn uniformly at random,
+/// so a tournament as big as the population always picks the shortest tour. Only the
+/// order of the lengths matters, so the pressure does not depend on how much they differ.
+pub struct TournamentSelection {
+    pub size: usize,
+}
+
+/// Linear rank selection: the tours are drawn with probabilities that fall linearly with
+/// their rank, from `pressure` times the average for the shortest tour to
+/// `2 - pressure` times the average for the longest, with `pressure` between 1 and 2.
+pub struct RankSelection {
+    pub pressure: f64,
+}
+
+/// Stochastic universal sampling: the roulette fitness, but a whole population of
+/// parents is drawn at once with evenly spaced pointers, so that every tour is picked
+/// within one of its expected number of times. The parents are handed out in random
+/// order.
+pub struct StochasticUniversalSampling;
+
+/// The fitness of [`RouletteSelection`].
+fn roulette_fitnesses(lengths: &[f32]) -> Vec<f32> {
+    let epsilon: f32 = 1e-6;
+    let min_length = lengths.iter().copied().fold(f32::MAX, f32::min);
+    let max_length = lengths.iter().copied().fold(f32::MIN, f32::max);
+    lengths
+        .iter()
+        .map(|&length| (max_length - length + epsilon) / (max_length - min_length + epsilon))
+        .collect()
+}
+
+struct WeightedSampler(WeightedIndex<f32>);
+
+impl ParentSampler for WeightedSampler {
+    fn sample(&mut self, mut rng: &mut dyn RngCore) -> usize {
+        self.0.sample(&mut rng)
+    }
+}
+
+impl SelectionStrategy for RouletteSelection {
+    fn sampler(&self, lengths: &[f32]) -> Box<dyn ParentSampler> {
+        Box::new(WeightedSampler(
+            WeightedIndex::new(roulette_fitnesses(lengths))
+                .expect("Failed to create WeightedIndex"),
+        ))
+    }
+
+    fn name(&self) -> &'static str {
+        "roulette"
+    }
+}
+
+struct TournamentSampler {
+    lengths: Vec<f32>,
+    size: usize,
+}
+
+impl ParentSampler for TournamentSampler {
+    fn sample(&mut self, rng: &mut dyn RngCore) -> usize {
+        let n = self.lengths.len();
+        index::sample(rng, n, self.size.clamp(1, n))
+            .into_iter()
+            .min_by(|&a, &b| self.lengths[a].total_cmp(&self.lengths[b]))
+            .unwrap()
+    }
+}
+
+impl SelectionStrategy for TournamentSelection {
+    fn sampler(&self, lengths: &[f32]) -> Box<dyn ParentSampler> {
+        Box::new(TournamentSampler {
+            lengths: lengths.to_vec(),
+            size: self.size,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "tournament"
+    }
+}
+
+impl SelectionStrategy for RankSelection {
+    fn sampler(&self, lengths: &[f32]) -> Box<dyn ParentSampler> {
+        let n = lengths.len();
+        let pressure = self.pressure.clamp(1.0, 2.0) as f32;
+        let mut by_length = (0..n).collect::<Vec<_>>();
+        by_length.sort_by(|&a, &b| lengths[a].total_cmp(&lengths[b]));
+        let mut weights = vec![0.0; n];
+        for (rank, &index) in by_length.iter().enumerate() {
+            // the shortest tour has rank 0 and weight `pressure`, the longest `2 - pressure`
+            let share = if n > 1 {
+                rank as f32 / (n - 1) as f32
+            } else {
+                0.0
+            };
+            weights[index] = pressure - 2.0 * (pressure - 1.0) * share;
+        }
+        // with the full pressure of 2 the longest tour has weight 0, which is allowed as
+        // long as another tour has some
+        Box::new(WeightedSampler(
+            WeightedIndex::new(weights).expect("Failed to create WeightedIndex"),
+        ))
+    }
+
+    fn name(&self) -> &'static str {
+        "rank"
+    }
+}
+
+struct UniversalSampler {
+    /// The cumulative roulette fitness up to and including every tour.
+    cumulative: Vec<f64>,
+    /// The parents of the current draw that were not handed out yet.
+    pending: Vec<usize>,
+}
+
+impl ParentSampler for UniversalSampler {
+    fn sample(&mut self, rng: &mut dyn RngCore) -> usize {
+        if self.pending.is_empty() {
+            let n = self.cumulative.len();
+            let total = self.cumulative[n - 1];
+            let spacing = total / n as f64;
+            let mut pointer = rng.random::<f64>() * spacing;
+            let mut index = 0;
+            for _ in 0..n {
+                while index + 1 < n && self.cumulative[index] <= pointer {
+                    index += 1;
+                }
+                self.pending.push(index);
+                pointer += spacing;
+            }
+            self.pending.shuffle(rng);
+        }
+        self.pending.pop().unwrap()
+    }
+}
+
+impl SelectionStrategy for StochasticUniversalSampling {
+    fn sampler(&self, lengths: &[f32]) -> Box<dyn ParentSampler> {
+        let cumulative = roulette_fitnesses(lengths)
+            .into_iter()
+            .scan(0.0, |sum, fitness| {
+                *sum += f64::from(fitness);
+                Some(*sum)
+            })
+            .collect::<Vec<_>>();
+        Box::new(UniversalSampler {
+            pending: Vec::with_capacity(cumulative.len()),
+            cumulative,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "stochastic universal"
+    }
+}
@@ -0,0 +1,326 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+use crate::{
+    construction::{Construction, build_start_tour},
+    genetic::DEFAULT_SEED,
+    local_search::{LOCAL_SEARCH_NEIGHBORS, distance},
+    neighbors::nearest_neighbor_lists,
+    or_opt::{OR_OPT_MAX_SEGMENT_LENGTH, move_segment},
+    problem::{Problem, Solution},
+    tour::ArrayTour,
+    two_opt::MAX_FLIP_COST,
+};
+
+/// How likely a move of average uphill size is accepted at the start of the schedule.
+const INITIAL_ACCEPTANCE: f64 = 0.05;
+/// The share of uphill moves the adaptive schedule aims to accept at the end, when the
+/// search is all but greedy.
+const FINAL_ACCEPTANCE: f64 = 1e-4;
+/// Where the geometric schedule ends, as a fraction of the start temperature.
+const FINAL_TEMPERATURE_RATIO: f64 = 0.01;
+/// How many moves are proposed between two updates of the temperature.
+const EPOCH_LENGTH: u64 = 1024;
+/// How much the adaptive schedule changes the temperature per epoch.
+const ADAPTIVE_STEP: f64 = 1.1;
+/// Each reheat starts at this fraction of the previous start temperature.
+const REHEAT_FACTOR: f64 = 0.5;
+/// How many random moves estimate the average uphill move.
+const TEMPERATURE_SAMPLES: usize = 1000;
+
+/// How the temperature falls over an annealing cycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoolingSchedule {
+    /// Falls geometrically with the elapsed share of the cycle, down to
+    /// `FINAL_TEMPERATURE_RATIO` of the start temperature.
+    Geometric,
+    /// Follows a target share of accepted uphill moves that falls geometrically from
+    /// `INITIAL_ACCEPTANCE` to `FINAL_ACCEPTANCE`: after every epoch the temperature rises
+    /// if fewer uphill moves were accepted than targeted and falls otherwise.
+    Adaptive,
+}
+
+/// A move proposed to the annealer, with the segment moves of Or-opt written as in
+/// [`move_segment`].
+enum Move {
+    TwoOpt([u32; 4]),
+    OrOpt {
+        segment: (u32, u32),
+        forward: bool,
+        end: u32,
+        edge: (u32, u32),
+    },
+}
+
+impl Move {
+    fn apply(&self, tour: &mut ArrayTour) {
+        match *self {
+            Move::TwoOpt([a, b, c, d]) => tour.flip(a, b, c, d),
+            Move::OrOpt {
+                segment,
+                forward,
+                end,
+                edge,
+            } => move_segment(tour, segment, forward, end, edge),
+        }
+    }
+}
+
+/// Simulated annealing over random 2-opt and Or-opt moves to candidate neighbors, each
+/// evaluated in O(1) from the edges it changes. The time left after building the start
+/// tour is split into annealing cycles; every reheat starts again from the best tour.
+pub struct SimulatedAnnealing {
+    pub problem: Rc<Problem>,
+    /// Tours to start from, e.g. the result of a previous run.
+    pub initial_solutions: Vec<Solution>,
+    /// Heuristics that build start tours from the first city, next to the initial
+    /// solutions. The search starts from the shortest of all of them.
+    pub constructions: Vec<Construction>,
+    pub cooling_schedule: CoolingSchedule,
+    /// How many times the temperature is raised again after a cycle.
+    pub reheats: u32,
+    rng: RefCell<StdRng>,
+}
+
+impl SimulatedAnnealing {
+    pub fn new(problem: Rc<Problem>) -> Self {
+        Self::with_seed(problem, DEFAULT_SEED)
+    }
+
+    pub fn with_seed(problem: Rc<Problem>, seed: u64) -> Self {
+        Self {
+            problem,
+            initial_solutions: Vec::new(),
+            constructions: vec![Construction::GreedyEdge],
+            cooling_schedule: CoolingSchedule::Geometric,
+            reheats: 0,
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// Anneals until `time_limit_secs` seconds after `timer` started, and returns the best
+    /// tour found.
+    pub fn solve(&self, timer: &Instant, time_limit_secs: u64) -> Solution {
+        let deadline = *timer + Duration::from_secs(time_limit_secs);
+        let problem = &self.problem;
+        let start = build_start_tour(
+            problem,
+            &self.constructions,
+            &self.initial_solutions,
+            deadline,
+        );
+        // smaller tours have no moves that change them
+        if problem.cities.len() < 5 {
+            return start;
+        }
+        let neighbor_lists = nearest_neighbor_lists(problem, LOCAL_SEARCH_NEIGHBORS);
+        let mut rng = self.rng.borrow_mut();
+        let mut annealer = Annealer {
+            problem,
+            neighbor_lists: &neighbor_lists,
+            rng: &mut *rng,
+            tour: ArrayTour::from_solution(&start),
+            distance: start.total_distance() as f64,
+            best_distance: start.total_distance() as f64,
+            best_order: start.order_without_loop.clone(),
+            tour_is_best: true,
+        };
+        let mut start_temperature = annealer.average_uphill() / -INITIAL_ACCEPTANCE.ln();
+        let now = Instant::now();
+        let cycle_length = deadline.saturating_duration_since(now) / (self.reheats + 1);
+        for cycle in 0..=self.reheats {
+            if cycle > 0 {
+                annealer.restore_best();
+                start_temperature *= REHEAT_FACTOR;
+            }
+            let cycle_end = now + cycle_length * (cycle + 1);
+            annealer.anneal(start_temperature, cycle_end, self.cooling_schedule);
+        }
+        let mut order = annealer.into_best_order();
+        let position = order
+            .iter()
+            .position(|&city| city == start.order_without_loop[0])
+            .unwrap();
+        order.rotate_left(position);
+        Solution::new(order, Rc::downgrade(problem), None)
+    }
+}
+
+/// The state of an annealing run. The best tour is copied out only when the annealer is
+/// about to leave it uphill.
+struct Annealer<'a, R: Rng> {
+    problem: &'a Problem,
+    neighbor_lists: &'a [Vec<u32>],
+    rng: &'a mut R,
+    tour: ArrayTour,
+    distance: f64,
+    best_distance: f64,
+    best_order: Vec<u32>,
+    tour_is_best: bool,
+}
+
+impl<R: Rng> Annealer<'_, R> {
+    fn propose(&mut self) -> Option<(f64, Move)> {
+        if self.rng.random_bool(0.5) {
+            self.propose_two_opt()
+        } else {
+            self.propose_or_opt()
+        }
+    }
+
+    /// A 2-opt move that connects a random city to one of its candidates.
+    fn propose_two_opt(&mut self) -> Option<(f64, Move)> {
+        let (tour, problem) = (&self.tour, self.problem);
+        let a = self.rng.random_range(0..tour.len()) as u32;
+        let &c = self.neighbor_lists[a as usize].choose(self.rng)?;
+        let forward = self.rng.random_bool(0.5);
+        let b = tour.succ(a, forward);
+        let d = tour.succ(c, forward);
+        if c == b || d == a || tour.flip_cost(a, b, c) > MAX_FLIP_COST {
+            return None;
+        }
+        let delta = distance(problem, a, c) + distance(problem, b, d)
+            - distance(problem, a, b)
+            - distance(problem, c, d);
+        Some((delta, Move::TwoOpt([a, b, c, d])))
+    }
+
+    /// An Or-opt move of a random segment next to a candidate of one of its ends.
+    fn propose_or_opt(&mut self) -> Option<(f64, Move)> {
+        let (tour, problem) = (&self.tour, self.problem);
+        let length = self.rng.random_range(1..=OR_OPT_MAX_SEGMENT_LENGTH);
+        if length + 3 > tour.len() {
+            return None;
+        }
+        let first = self.rng.random_range(0..tour.len()) as u32;
+        let forward = self.rng.random_bool(0.5);
+        let mut last = first;
+        for _ in 1..length {
+            last = tour.succ(last, forward);
+        }
+        let (end, other_end) = if self.rng.random_bool(0.5) {
+            (first, last)
+        } else {
+            (last, first)
+        };
+        let &c = self.neighbor_lists[end as usize].choose(self.rng)?;
+        let e = if self.rng.random_bool(0.5) {
+            tour.next(c)
+        } else {
+            tour.prev(c)
+        };
+        if tour.between(first, c, last, forward) || tour.between(first, e, last, forward) {
+            return None;
+        }
+        let before = tour.succ(first, !forward);
+        let after = tour.succ(last, forward);
+        let delta = distance(problem, c, end)
+            + distance(problem, other_end, e)
+            + distance(problem, before, after)
+            - distance(problem, before, first)
+            - distance(problem, last, after)
+            - distance(problem, c, e);
+        let or_opt_move = Move::OrOpt {
+            segment: (first, last),
+            forward,
+            end,
+            edge: (c, e),
+        };
+        Some((delta, or_opt_move))
+    }
+
+    /// The mean length increase of the uphill moves among random proposals.
+    fn average_uphill(&mut self) -> f64 {
+        let (mut total, mut count) = (0.0, 0);
+        for _ in 0..TEMPERATURE_SAMPLES {
+            if let Some((delta, _)) = self.propose()
+                && delta > 0.0
+            {
+                total += delta;
+                count += 1;
+            }
+        }
+        if count == 0 {
+            1.0
+        } else {
+            total / count as f64
+        }
+    }
+
+    /// One annealing cycle from `start_temperature` until `end`.
+    fn anneal(&mut self, start_temperature: f64, end: Instant, schedule: CoolingSchedule) {
+        let start = Instant::now();
+        let length = end.saturating_duration_since(start).as_secs_f64();
+        let final_temperature = start_temperature * FINAL_TEMPERATURE_RATIO;
+        let mut temperature = start_temperature;
+        let (mut uphill_proposed, mut uphill_accepted) = (0u64, 0u64);
+        for iteration in 0.. {
+            if iteration % EPOCH_LENGTH == 0 {
+                let now = Instant::now();
+                if now >= end {
+                    break;
+                }
+                let progress = now.duration_since(start).as_secs_f64() / length;
+                match schedule {
+                    CoolingSchedule::Geometric => {
+                        temperature = start_temperature
+                            * (final_temperature / start_temperature).powf(progress);
+                    }
+                    CoolingSchedule::Adaptive if uphill_proposed > 0 => {
+                        let target = INITIAL_ACCEPTANCE
+                            * (FINAL_ACCEPTANCE / INITIAL_ACCEPTANCE).powf(progress);
+                        let rate = uphill_accepted as f64 / uphill_proposed as f64;
+                        if rate < target {
+                            temperature *= ADAPTIVE_STEP;
+                        } else {
+                            temperature /= ADAPTIVE_STEP;
+                        }
+                        (uphill_proposed, uphill_accepted) = (0, 0);
+                    }
+                    CoolingSchedule::Adaptive => {}
+                }
+            }
+            let Some((delta, proposal)) = self.propose() else {
+                continue;
+            };
+            if delta > 0.0 {
+                uphill_proposed += 1;
+                if self.rng.random::<f64>() >= (-delta / temperature).exp() {
+                    continue;
+                }
+                uphill_accepted += 1;
+                if self.tour_is_best {
+                    self.best_order.copy_from_slice(self.tour.order());
+                    self.tour_is_best = false;
+                }
+            }
+            proposal.apply(&mut self.tour);
+            self.distance += delta;
+            if self.distance < self.best_distance {
+                self.best_distance = self.distance;
+                self.tour_is_best = true;
+            }
+        }
+    }
+
+    fn restore_best(&mut self) {
+        if !self.tour_is_best {
+            self.tour = ArrayTour::new(self.best_order.clone());
+            self.distance = self.best_distance;
+            self.tour_is_best = true;
+        }
+    }
+
+    fn into_best_order(self) -> Vec<u32> {
+        if self.tour_is_best {
+            self.tour.order().to_vec()
+        } else {
+            self.best_order
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::rc::Rc;
+
+use crate::problem::{City, Problem, Solution};
+
+/// Bits per axis of the curve keys; three axes fit in a `u64`.
+const BITS_PER_AXIS: u32 = 21;
+
+/// Maps every city to a point of a `2^BITS_PER_AXIS` grid. All axes are shifted by the
+/// same amount so the grid keeps the aspect ratio of the instance.
+fn grid_coordinates(cities: &[City]) -> Vec<[u32; 3]> {
+    let min = [
+        cities.iter().map(|c| c.x).min().unwrap_or(0),
+        cities.iter().map(|c| c.y).min().unwrap_or(0),
+        cities.iter().map(|c| c.z).min().unwrap_or(0),
+    ];
+    let max_extent = cities
+        .iter()
+        .map(|c| (c.x - min[0]).max(c.y - min[1]).max(c.z - min[2]))
+        .max()
+        .unwrap_or(0);
+    let extent_bits = u32::BITS - max_extent.leading_zeros();
+    let shift = extent_bits.saturating_sub(BITS_PER_AXIS);
+    cities
+        .iter()
+        .map(|c| {
+            [
+                (c.x - min[0]) >> shift,
+                (c.y - min[1]) >> shift,
+                (c.z - min[2]) >> shift,
+            ]
+        })
+        .collect()
+}
+
+/// Interleaves the bits of the three axes, most significant bits first.
+fn interleave(axes: [u32; 3]) -> u64 {
+    let mut key = 0u64;
+    for bit in (0..BITS_PER_AXIS).rev() {
+        for axis in axes {
+            key = (key << 1) | ((axis >> bit) & 1) as u64;
+        }
+    }
+    key
+}
+
+/// The position of a grid point along the 3D Hilbert curve, following Skilling's
+/// "Programming the Hilbert curve" (2004): the axes are turned into the transposed
+/// Hilbert index, whose interleaved bits are the index itself.
+fn hilbert_key(mut axes: [u32; 3]) -> u64 {
+    let highest_bit = 1u32 << (BITS_PER_AXIS - 1);
+    // inverse undo of the excess work
+    let mut q = highest_bit;
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..3 {
+            if axes[i] & q != 0 {
+                axes[0] ^= p;
+            } else {
+                let t = (axes[0] ^ axes[i]) & p;
+                axes[0] ^= t;
+                axes[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+    // Gray encode
+    for i in 1..3 {
+        axes[i] ^= axes[i - 1];
+    }
+    let mut t = 0;
+    let mut q = highest_bit;
+    while q > 1 {
+        if axes[2] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+    for axis in axes.iter_mut() {
+        *axis ^= t;
+    }
+    interleave(axes)
+}
+
+fn from_curve_order(
+    problem: &Rc<Problem>,
+    start_index: usize,
+    key: fn([u32; 3]) -> u64,
+) -> Solution {
+    assert!(start_index < problem.cities.len());
+    let keys = grid_coordinates(&problem.cities)
+        .into_iter()
+        .map(key)
+        .collect::<Vec<_>>();
+    let mut order = (0..problem.cities.len() as u32).collect::<Vec<_>>();
+    order.sort_unstable_by_key(|&city| (keys[city as usize], city));
+    let start_position = order
+        .iter()
+        .position(|&city| city == start_index as u32)
+        .unwrap();
+    order.rotate_left(start_position);
+    Solution::new(order, Rc::downgrade(problem), None)
+}
+
+impl Solution {
+    /// Visits the cities in the order of a 3D Hilbert curve through their bounding cube.
+    /// Takes O(n log n) time and no distance computations, so it is the fallback for
+    /// instances too large for the other constructions. Rotated to start at `start_index`.
+    pub fn from_hilbert_curve(problem: &Rc<Problem>, start_index: usize) -> Self {
+        from_curve_order(problem, start_index, hilbert_key)
+    }
+
+    /// Visits the cities in Morton (Z-order). Cheaper to compute than the Hilbert curve
+    /// but it jumps between octants, so the tours are noticeably longer.
+    pub fn from_morton_order(problem: &Rc<Problem>, start_index: usize) -> Self {
+        from_curve_order(problem, start_index, interleave)
+    }
+}
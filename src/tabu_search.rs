@@ -0,0 +1,327 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use crate::{
+    construction::{Construction, build_start_tour},
+    genetic::DEFAULT_SEED,
+    local_search::{LOCAL_SEARCH_NEIGHBORS, LocalSearch, deadline_passed, distance},
+    neighbors::nearest_neighbor_lists,
+    or_opt::move_segment,
+    problem::{Problem, Solution},
+    tour::ArrayTour,
+    two_opt::MAX_FLIP_COST,
+};
+
+/// For how many iterations an edge that a move removed may not be added again.
+pub const TABU_TENURE: u64 = 20;
+/// How many cities have their moves evaluated per iteration, next to the cities whose
+/// edges the last move changed. Smaller instances evaluate every city.
+const TABU_SAMPLE_SIZE: usize = 16;
+/// How many iterations without a shorter tour than the best of the current start end it
+/// and restart the search from another nearest neighbor tour.
+const TABU_RESTART_ITERATIONS: u64 = 2000;
+
+/// A move between candidate neighbors.
+#[derive(Clone, Copy)]
+enum Move {
+    /// The 2-opt move of [`ArrayTour::flip`].
+    TwoOpt([u32; 4]),
+    /// Exchanges the positions of two cities that are not adjacent.
+    Swap(u32, u32),
+    /// Moves a city between the adjacent cities of the edge.
+    Relocate(u32, (u32, u32)),
+}
+
+/// An evaluated move with the edges it removes and adds, where moves that change fewer
+/// than four edges repeat one of them.
+#[derive(Clone, Copy)]
+struct Candidate {
+    delta: f64,
+    tabu_move: Move,
+    removed: [(u32, u32); 4],
+    added: [(u32, u32); 4],
+}
+
+impl Candidate {
+    fn apply(&self, tour: &mut ArrayTour) {
+        match self.tabu_move {
+            Move::TwoOpt([a, b, c, d]) => tour.flip(a, b, c, d),
+            Move::Swap(a, c) => {
+                // prev(a) [a..c] next(c) -> prev(a) c [..]^r a next(c) -> prev(a) c [..] a next(c)
+                let (before, after) = (tour.prev(a), tour.next(c));
+                let (a_next, c_prev) = (tour.next(a), tour.prev(c));
+                tour.flip(before, a, c, after);
+                tour.flip(c, c_prev, a_next, a);
+            }
+            Move::Relocate(city, edge) => move_segment(tour, (city, city), true, city, edge),
+        }
+    }
+}
+
+/// Tabu search over 2-opt, swap and relocate moves to candidate neighbors. Every iteration
+/// applies the best move among those of a sample of cities, even when it lengthens the
+/// tour, unless it adds an edge removed within the last `TABU_TENURE` iterations and does
+/// not lead to a tour shorter than the best so far. When a start stops improving, the
+/// search restarts from the nearest neighbor tour of another city.
+pub struct TabuSearch {
+    pub problem: Rc<Problem>,
+    /// Tours to start from, e.g. the result of a previous run.
+    pub initial_solutions: Vec<Solution>,
+    /// Heuristics that build start tours from the first city, next to the initial
+    /// solutions. The first start is the shortest of all of them.
+    pub constructions: Vec<Construction>,
+    /// Local searches that polish every start tour before the tabu search takes over.
+    pub local_searches: Vec<LocalSearch>,
+    pub tenure: u64,
+    rng: RefCell<StdRng>,
+}
+
+impl TabuSearch {
+    pub fn new(problem: Rc<Problem>) -> Self {
+        Self::with_seed(problem, DEFAULT_SEED)
+    }
+
+    pub fn with_seed(problem: Rc<Problem>, seed: u64) -> Self {
+        Self {
+            problem,
+            initial_solutions: Vec::new(),
+            constructions: vec![Construction::NearestNeighbor],
+            local_searches: vec![LocalSearch::TwoOpt, LocalSearch::OrOpt],
+            tenure: TABU_TENURE,
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// Searches until `time_limit_secs` seconds after `timer` started, and returns the best
+    /// tour found.
+    pub fn solve(&self, timer: &Instant, time_limit_secs: u64) -> Solution {
+        let deadline = *timer + Duration::from_secs(time_limit_secs);
+        let problem = &self.problem;
+        let num_cities = problem.cities.len();
+        let start = build_start_tour(
+            problem,
+            &self.constructions,
+            &self.initial_solutions,
+            deadline,
+        );
+        let neighbor_lists = nearest_neighbor_lists(problem, LOCAL_SEARCH_NEIGHBORS);
+        let start = start.improve_with(&self.local_searches, &neighbor_lists, Some(deadline));
+        // smaller tours have no moves that change them
+        if num_cities < 5 {
+            return start;
+        }
+        let first = start.order_without_loop[0];
+        let mut rng = self.rng.borrow_mut();
+        // the cities whose nearest neighbor tours the restarts begin with
+        let mut restart_cities = (0..num_cities as u32)
+            .filter(|&city| city != first)
+            .collect::<Vec<_>>();
+        restart_cities.shuffle(&mut *rng);
+        let mut search = Search {
+            problem,
+            neighbor_lists: &neighbor_lists,
+            tenure: self.tenure.min(num_cities as u64 / 4).max(1),
+            tabu_until: HashMap::new(),
+            tour: ArrayTour::from_solution(&start),
+            distance: start.total_distance() as f64,
+            best_distance: start.total_distance() as f64,
+            best_order: start.order_without_loop.clone(),
+            tour_is_best: true,
+        };
+        loop {
+            search.run(&mut *rng, deadline);
+            let Some(city) = restart_cities.pop() else {
+                break;
+            };
+            // a restart that cannot be built well before the deadline would only overrun it
+            if !Construction::NearestNeighbor.fits_before(num_cities, deadline) {
+                break;
+            }
+            let restart = Solution::from_nearest_neighbor(problem, city as usize).improve_with(
+                &self.local_searches,
+                &neighbor_lists,
+                Some(deadline),
+            );
+            search.restart(&restart);
+        }
+        let mut order = search.into_best_order();
+        let position = order.iter().position(|&city| city == first).unwrap();
+        order.rotate_left(position);
+        Solution::new(order, Rc::downgrade(problem), None)
+    }
+}
+
+/// The state of a tabu search. The best tour is copied out only when the search is about
+/// to leave it.
+struct Search<'a> {
+    problem: &'a Problem,
+    neighbor_lists: &'a [Vec<u32>],
+    tenure: u64,
+    /// The iteration until which adding each edge, with the smaller city first, is tabu.
+    tabu_until: HashMap<(u32, u32), u64>,
+    tour: ArrayTour,
+    distance: f64,
+    best_distance: f64,
+    best_order: Vec<u32>,
+    tour_is_best: bool,
+}
+
+impl Search<'_> {
+    /// Moves from the current tour until `TABU_RESTART_ITERATIONS` iterations passed
+    /// without a shorter tour than the best one since the start, or until the deadline.
+    fn run(&mut self, rng: &mut impl Rng, deadline: Instant) {
+        let num_cities = self.tour.len();
+        let mut start_best = self.distance;
+        let mut last_improvement = 0;
+        let mut changed = Vec::new();
+        for iteration in 0.. {
+            if iteration - last_improvement >= TABU_RESTART_ITERATIONS
+                || deadline_passed(Some(deadline))
+            {
+                return;
+            }
+            if iteration % self.tenure == 0 {
+                self.tabu_until.retain(|_, &mut until| until > iteration);
+            }
+            let mut best: Option<Candidate> = None;
+            let mut consider = |candidate: Candidate| {
+                let admissible = self.distance + candidate.delta < self.best_distance - 1e-9
+                    || candidate
+                        .added
+                        .iter()
+                        .all(|edge| self.tabu_until.get(edge).is_none_or(|&u| u <= iteration));
+                if admissible && best.is_none_or(|best| candidate.delta < best.delta) {
+                    best = Some(candidate);
+                }
+            };
+            if num_cities <= TABU_SAMPLE_SIZE {
+                for city in 0..num_cities as u32 {
+                    self.evaluate(city, &mut consider);
+                }
+            } else {
+                for &city in &changed {
+                    self.evaluate(city, &mut consider);
+                }
+                for _ in 0..TABU_SAMPLE_SIZE {
+                    self.evaluate(rng.random_range(0..num_cities) as u32, &mut consider);
+                }
+            }
+            let Some(candidate) = best else {
+                changed.clear();
+                continue;
+            };
+            if self.tour_is_best && candidate.delta >= 0.0 {
+                self.best_order.copy_from_slice(self.tour.order());
+                self.tour_is_best = false;
+            }
+            candidate.apply(&mut self.tour);
+            self.distance += candidate.delta;
+            for (a, b) in candidate.removed {
+                self.tabu_until.insert(edge(a, b), iteration + self.tenure);
+            }
+            changed.clear();
+            changed.extend(candidate.added.iter().flat_map(|&(a, b)| [a, b]));
+            changed.dedup();
+            if self.distance < self.best_distance - 1e-9 {
+                self.best_distance = self.distance;
+                self.tour_is_best = true;
+            }
+            if self.distance < start_best - 1e-9 {
+                start_best = self.distance;
+                last_improvement = iteration;
+            }
+        }
+    }
+
+    /// Passes every move that connects `a` to one of its candidates to `consider`.
+    fn evaluate(&self, a: u32, consider: &mut impl FnMut(Candidate)) {
+        let (tour, problem) = (&self.tour, self.problem);
+        let dist = |x, y| distance(problem, x, y);
+        let (a_prev, a_next) = (tour.prev(a), tour.next(a));
+        for &c in &self.neighbor_lists[a as usize] {
+            for forward in [true, false] {
+                let b = tour.succ(a, forward);
+                let d = tour.succ(c, forward);
+                if c == b || d == a || tour.flip_cost(a, b, c) > MAX_FLIP_COST {
+                    continue;
+                }
+                consider(Candidate {
+                    delta: dist(a, c) + dist(b, d) - dist(a, b) - dist(c, d),
+                    tabu_move: Move::TwoOpt([a, b, c, d]),
+                    removed: [edge(a, b), edge(c, d), edge(a, b), edge(c, d)],
+                    added: [edge(a, c), edge(b, d), edge(a, c), edge(b, d)],
+                });
+            }
+            let (c_prev, c_next) = (tour.prev(c), tour.next(c));
+            if c != a_prev && c != a_next && tour.flip_cost(a_prev, a, c) <= MAX_FLIP_COST {
+                consider(Candidate {
+                    delta: dist(a_prev, c) + dist(c, a_next) + dist(c_prev, a) + dist(a, c_next)
+                        - dist(a_prev, a)
+                        - dist(a, a_next)
+                        - dist(c_prev, c)
+                        - dist(c, c_next),
+                    tabu_move: Move::Swap(a, c),
+                    removed: [
+                        edge(a_prev, a),
+                        edge(a, a_next),
+                        edge(c_prev, c),
+                        edge(c, c_next),
+                    ],
+                    added: [
+                        edge(a_prev, c),
+                        edge(c, a_next),
+                        edge(c_prev, a),
+                        edge(a, c_next),
+                    ],
+                });
+            }
+            for e in [c_prev, c_next] {
+                if e == a {
+                    continue;
+                }
+                consider(Candidate {
+                    delta: dist(c, a) + dist(a, e) + dist(a_prev, a_next)
+                        - dist(a_prev, a)
+                        - dist(a, a_next)
+                        - dist(c, e),
+                    tabu_move: Move::Relocate(a, (c, e)),
+                    removed: [edge(a_prev, a), edge(a, a_next), edge(c, e), edge(c, e)],
+                    added: [edge(c, a), edge(a, e), edge(a_prev, a_next), edge(c, a)],
+                });
+            }
+        }
+    }
+
+    fn restart(&mut self, solution: &Solution) {
+        if self.tour_is_best {
+            self.best_order.copy_from_slice(self.tour.order());
+            self.tour_is_best = false;
+        }
+        self.tour = ArrayTour::from_solution(solution);
+        self.distance = solution.total_distance() as f64;
+        self.tabu_until.clear();
+        if self.distance < self.best_distance - 1e-9 {
+            self.best_distance = self.distance;
+            self.tour_is_best = true;
+        }
+    }
+
+    fn into_best_order(self) -> Vec<u32> {
+        if self.tour_is_best {
+            self.tour.order().to_vec()
+        } else {
+            self.best_order
+        }
+    }
+}
+
+/// The key of the undirected edge `(a, b)`.
+fn edge(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b), a.max(b))
+}
@@ -0,0 +1,192 @@
+use std::time::Instant;
+
+use crate::{
+    local_search::{ActiveCities, LocalSearch, deadline_passed, distance},
+    problem::{Problem, Solution},
+    tour::ArrayTour,
+    two_opt::MAX_FLIP_COST,
+};
+
+/// The ways to reconnect the tour `t1 [X] [Y] q` after removing three edges, with the
+/// segments written in the direction of the search.
+#[derive(Clone, Copy)]
+enum Reconnection {
+    /// `t1 Y X q`, the segments exchanged without reversal.
+    Exchange,
+    /// `t1 Y X^r q`
+    ExchangeReversingFirst,
+    /// `t1 Y^r X q`
+    ExchangeReversingSecond,
+    /// `t1 X^r Y^r q`, both segments reversed in place.
+    ReverseBoth,
+}
+
+/// A 3-opt move: the tour `t1 [x.0..x.1] [y.0..y.1] q` in the search direction.
+struct Move {
+    t1: u32,
+    x: (u32, u32),
+    y: (u32, u32),
+    q: u32,
+    reconnection: Reconnection,
+}
+
+impl Move {
+    /// How many cities the flips of this move reverse, roughly.
+    fn cost(&self, tour: &ArrayTour, forward: bool) -> usize {
+        let n = tour.len();
+        let x = tour.path_length(self.x.0, self.x.1, forward);
+        let y = tour.path_length(self.y.0, self.y.1, forward);
+        let shorter_side = |length: usize| length.min(n - length);
+        shorter_side(x + y) + shorter_side(x) + shorter_side(y)
+    }
+
+    fn apply(&self, tour: &mut ArrayTour) {
+        let Move { t1, x, y, q, .. } = *self;
+        match self.reconnection {
+            Reconnection::Exchange => tour.swap_segments(t1, x, y, q, false, false),
+            Reconnection::ExchangeReversingFirst => tour.swap_segments(t1, x, y, q, true, false),
+            Reconnection::ExchangeReversingSecond => tour.swap_segments(t1, x, y, q, false, true),
+            Reconnection::ReverseBoth => {
+                // t1 X^r Y q, then t1 X^r Y^r q
+                tour.flip(t1, x.0, x.1, y.0);
+                tour.flip(x.0, y.0, y.1, q);
+            }
+        }
+    }
+}
+
+/// Looks for an improving sequential 3-opt move that removes the tour edge from `t1` in
+/// direction `forward`. With `t2` after `t1`, the edge `(t2, t3)` goes to a candidate of
+/// `t2`, `t4` is a tour neighbor of `t3`, the edge `(t4, t5)` goes to a candidate of `t4`
+/// and `(t6, t1)` closes the tour. Each partial sum of gains must stay positive.
+fn improve_city(
+    tour: &mut ArrayTour,
+    problem: &Problem,
+    neighbor_lists: &[Vec<u32>],
+    t1: u32,
+    forward: bool,
+) -> Option<(f64, [u32; 6])> {
+    let t2 = tour.succ(t1, forward);
+    let g0 = distance(problem, t1, t2);
+    for &t3 in &neighbor_lists[t2 as usize] {
+        let g1 = g0 - distance(problem, t2, t3);
+        if g1 <= 0.0 {
+            break;
+        }
+        if t3 == t1 {
+            continue;
+        }
+        for t4_follows_t3 in [true, false] {
+            let t4 = tour.succ(t3, forward == t4_follows_t3);
+            if t4 == t1 || t4 == t2 {
+                continue;
+            }
+            let g1 = g1 + distance(problem, t3, t4);
+            for &t5 in &neighbor_lists[t4 as usize] {
+                let g2 = g1 - distance(problem, t4, t5);
+                if g2 <= 0.0 {
+                    break;
+                }
+                // t3 would only give back the edge just removed
+                if t5 == t1 || t5 == t3 {
+                    continue;
+                }
+                let mut options = [None, None];
+                if t4_follows_t3 {
+                    // t1 [t2..t3] [t4..]: t5 must break up t2..t3
+                    if !tour.between(t2, t5, t3, forward) {
+                        continue;
+                    }
+                    let t6 = tour.succ(t5, forward);
+                    options[0] = Some((t6, (t2, t5), (t6, t3), Reconnection::Exchange));
+                    if t5 != t2 {
+                        let t6 = tour.succ(t5, !forward);
+                        options[1] = Some((t6, (t2, t6), (t5, t3), Reconnection::ReverseBoth));
+                    }
+                } else if tour.between(t2, t5, t4, forward) {
+                    // t1 [t2..t5] [t6..t4] t3
+                    if t5 != t4 {
+                        let t6 = tour.succ(t5, forward);
+                        options[0] =
+                            Some((t6, (t2, t5), (t6, t4), Reconnection::ExchangeReversingFirst));
+                    }
+                } else {
+                    // t1 [t2..t4] [t3..t6] t5
+                    let t6 = tour.succ(t5, !forward);
+                    options[0] = Some((
+                        t6,
+                        (t2, t4),
+                        (t3, t6),
+                        Reconnection::ExchangeReversingSecond,
+                    ));
+                }
+                for (t6, x, y, reconnection) in options.into_iter().flatten() {
+                    let gain = g2 + distance(problem, t5, t6) - distance(problem, t6, t1);
+                    if gain <= 0.0 {
+                        continue;
+                    }
+                    let q = match reconnection {
+                        Reconnection::Exchange | Reconnection::ReverseBoth => t4,
+                        Reconnection::ExchangeReversingFirst => t3,
+                        Reconnection::ExchangeReversingSecond => t5,
+                    };
+                    let three_opt_move = Move {
+                        t1,
+                        x,
+                        y,
+                        q,
+                        reconnection,
+                    };
+                    if three_opt_move.cost(tour, forward) > MAX_FLIP_COST {
+                        continue;
+                    }
+                    three_opt_move.apply(tour);
+                    return Some((gain, [t1, t2, t3, t4, t5, t6]));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Or-3opt with candidate lists and don't-look bits: the sequential 3-opt moves that
+/// exchange two neighboring segments, optionally reversing one of them, which includes
+/// moving a segment of any length elsewhere, and the move that reverses both in place.
+/// Stops at a local optimum or when `deadline` passes, and returns the total gain.
+pub fn three_opt(
+    tour: &mut ArrayTour,
+    problem: &Problem,
+    neighbor_lists: &[Vec<u32>],
+    active: &mut ActiveCities,
+    deadline: Option<Instant>,
+) -> f64 {
+    let mut total_gain = 0.0;
+    // fewer cities leave no room for three distinct edges
+    if tour.len() < 6 {
+        return total_gain;
+    }
+    while let Some(t1) = active.pop() {
+        if deadline_passed(deadline) {
+            break;
+        }
+        for forward in [true, false] {
+            if let Some((gain, endpoints)) =
+                improve_city(tour, problem, neighbor_lists, t1, forward)
+            {
+                total_gain += gain;
+                for city in endpoints {
+                    active.push(city);
+                }
+                break;
+            }
+        }
+    }
+    total_gain
+}
+
+impl Solution {
+    /// The tour improved by [`three_opt`] until no move helps or `deadline` passes.
+    pub fn three_opt(&self, neighbor_lists: &[Vec<u32>], deadline: Option<Instant>) -> Solution {
+        self.improve_with(&[LocalSearch::ThreeOpt], neighbor_lists, deadline)
+    }
+}
@@ -0,0 +1,197 @@
+use crate::problem::Solution;
+
+/// A tour stored as the order of the cities plus the position of every city, so the
+/// neighbors of a city and the direction between three cities are O(1) lookups. The local
+/// searches change it only through [`ArrayTour::flip`], which reverses the shorter side.
+#[derive(Clone, Debug)]
+pub struct ArrayTour {
+    order: Vec<u32>,
+    position: Vec<u32>,
+    /// The position ranges reversed since the last checkpoint, if one was set.
+    journal: Option<Vec<(usize, usize)>>,
+}
+
+impl ArrayTour {
+    pub fn new(order: Vec<u32>) -> Self {
+        let mut position = vec![0u32; order.len()];
+        for (i, &city) in order.iter().enumerate() {
+            position[city as usize] = i as u32;
+        }
+        Self {
+            order,
+            position,
+            journal: None,
+        }
+    }
+
+    pub fn from_solution(solution: &Solution) -> Self {
+        Self::new(solution.order_without_loop.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn order(&self) -> &[u32] {
+        &self.order
+    }
+
+    /// The order rotated to start at `first`.
+    pub fn order_from(&self, first: u32) -> Vec<u32> {
+        let mut order = self.order.clone();
+        order.rotate_left(self.position(first));
+        order
+    }
+
+    pub fn position(&self, city: u32) -> usize {
+        self.position[city as usize] as usize
+    }
+
+    pub fn next(&self, city: u32) -> u32 {
+        let i = self.position(city) + 1;
+        if i == self.order.len() {
+            self.order[0]
+        } else {
+            self.order[i]
+        }
+    }
+
+    pub fn prev(&self, city: u32) -> u32 {
+        let i = self.position(city);
+        if i == 0 {
+            self.order[self.order.len() - 1]
+        } else {
+            self.order[i - 1]
+        }
+    }
+
+    /// `next` when `forward`, `prev` otherwise.
+    pub fn succ(&self, city: u32, forward: bool) -> u32 {
+        if forward {
+            self.next(city)
+        } else {
+            self.prev(city)
+        }
+    }
+
+    /// Whether `b` lies on the path from `a` to `c`, both ends included, in the given
+    /// direction.
+    pub fn between(&self, a: u32, b: u32, c: u32, forward: bool) -> bool {
+        let (a, c) = if forward { (a, c) } else { (c, a) };
+        let (a, b, c) = (self.position(a), self.position(b), self.position(c));
+        if a <= c {
+            a <= b && b <= c
+        } else {
+            b >= a || b <= c
+        }
+    }
+
+    /// The number of cities on the path from `a` to `b` in the given direction, both ends
+    /// included.
+    pub fn path_length(&self, a: u32, b: u32, forward: bool) -> usize {
+        let n = self.order.len();
+        let (from, to) = if forward {
+            (self.position(a), self.position(b))
+        } else {
+            (self.position(b), self.position(a))
+        };
+        (to + n - from) % n + 1
+    }
+
+    /// The 2-opt move: removes the edges `(a, b)` and `(c, d)` and adds `(a, c)` and
+    /// `(b, d)`. `b` must follow `a` in the same direction as `d` follows `c`.
+    pub fn flip(&mut self, a: u32, b: u32, c: u32, d: u32) {
+        if self.next(a) == b {
+            debug_assert_eq!(self.next(c), d);
+            self.reverse(self.position(b), self.position(c));
+        } else {
+            debug_assert_eq!(self.prev(a), b);
+            debug_assert_eq!(self.prev(c), d);
+            self.reverse(self.position(c), self.position(b));
+        }
+    }
+
+    /// Turns `p [x_first..x_last] [y_first..y_last] q`, consecutive in one direction,
+    /// into `p Y X q`, where each segment keeps its orientation unless asked to reverse.
+    pub fn swap_segments(
+        &mut self,
+        p: u32,
+        (x_first, x_last): (u32, u32),
+        (y_first, y_last): (u32, u32),
+        q: u32,
+        reverse_x: bool,
+        reverse_y: bool,
+    ) {
+        // p Y^r X^r q
+        self.flip(p, x_first, y_last, q);
+        let mut y_end = y_first;
+        if !reverse_y {
+            // p Y X^r q
+            self.flip(p, y_last, y_first, x_last);
+            y_end = y_last;
+        }
+        if !reverse_x {
+            self.flip(y_end, x_last, x_first, q);
+        }
+    }
+
+    /// Starts recording the changes to the tour, so that [`ArrayTour::rollback`] can
+    /// return to it.
+    pub fn checkpoint(&mut self) {
+        match &mut self.journal {
+            Some(journal) => journal.clear(),
+            None => self.journal = Some(Vec::new()),
+        }
+    }
+
+    /// Undoes every change since the last [`ArrayTour::checkpoint`].
+    pub fn rollback(&mut self) {
+        let mut journal = self.journal.take().expect("rollback without a checkpoint");
+        // reversing the same positions again restores them
+        for &(from, to) in journal.iter().rev() {
+            self.reverse(from, to);
+        }
+        journal.clear();
+        self.journal = Some(journal);
+    }
+
+    /// How many cities [`ArrayTour::flip`] would move for the same arguments.
+    pub fn flip_cost(&self, a: u32, b: u32, c: u32) -> usize {
+        let n = self.order.len();
+        let (from, to) = if self.next(a) == b {
+            (self.position(b), self.position(c))
+        } else {
+            (self.position(c), self.position(b))
+        };
+        let length = (to + n - from) % n + 1;
+        length.min(n - length)
+    }
+
+    /// Reverses the cyclic stretch of positions from `from` to `to`, or the rest of the
+    /// tour if that is shorter; both give the same cycle.
+    fn reverse(&mut self, from: usize, to: usize) {
+        if let Some(journal) = &mut self.journal {
+            journal.push((from, to));
+        }
+        let n = self.order.len();
+        let length = (to + n - from) % n + 1;
+        let (mut i, mut j, length) = if 2 * length > n {
+            ((to + 1) % n, (from + n - 1) % n, n - length)
+        } else {
+            (from, to, length)
+        };
+        for _ in 0..length / 2 {
+            let (city_i, city_j) = (self.order[i], self.order[j]);
+            self.order[i] = city_j;
+            self.order[j] = city_i;
+            self.position[city_j as usize] = i as u32;
+            self.position[city_i as usize] = j as u32;
+            i = if i + 1 == n { 0 } else { i + 1 };
+            j = if j == 0 { n - 1 } else { j - 1 };
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::time::Instant;
+
+use crate::{
+    local_search::{ActiveCities, LocalSearch, deadline_passed, distance},
+    problem::{Problem, Solution},
+    tour::ArrayTour,
+};
+
+/// Moves that would reverse more cities than this are skipped. It only matters for
+/// instances of more than twice as many cities, where such moves are rare but each one
+/// costs as much as thousands of short ones.
+pub const MAX_FLIP_COST: usize = 50_000;
+
+/// Looks for an improving 2-opt move that replaces the tour edge from `a` in direction
+/// `forward` by an edge from `a` to one of its candidates, and applies the first one found.
+/// The candidates are sorted by distance, so the search stops at the first candidate that
+/// is not closer than the current tour neighbor.
+fn improve_city(
+    tour: &mut ArrayTour,
+    problem: &Problem,
+    neighbor_lists: &[Vec<u32>],
+    a: u32,
+    forward: bool,
+) -> Option<(f64, [u32; 4])> {
+    let b = tour.succ(a, forward);
+    let ab = distance(problem, a, b);
+    for &c in &neighbor_lists[a as usize] {
+        let ac = distance(problem, a, c);
+        if ac >= ab {
+            break;
+        }
+        let d = tour.succ(c, forward);
+        if c == b || d == a {
+            continue;
+        }
+        let gain = ab + distance(problem, c, d) - ac - distance(problem, b, d);
+        if gain > 0.0 && tour.flip_cost(a, b, c) <= MAX_FLIP_COST {
+            tour.flip(a, b, c, d);
+            return Some((gain, [a, b, c, d]));
+        }
+    }
+    None
+}
+
+/// 2-opt with candidate lists and don't-look bits: every move replaces two tour edges
+/// `(a, b)` and `(c, d)` by `(a, c)` and `(b, d)`, where `c` is one of the nearest
+/// neighbors of `a`. The change in length is computed from the four edges alone. Stops
+/// at a local optimum or when `deadline` passes, and returns the total gain.
+pub fn two_opt(
+    tour: &mut ArrayTour,
+    problem: &Problem,
+    neighbor_lists: &[Vec<u32>],
+    active: &mut ActiveCities,
+    deadline: Option<Instant>,
+) -> f64 {
+    let mut total_gain = 0.0;
+    while let Some(a) = active.pop() {
+        if deadline_passed(deadline) {
+            break;
+        }
+        for forward in [true, false] {
+            if let Some((gain, endpoints)) = improve_city(tour, problem, neighbor_lists, a, forward)
+            {
+                total_gain += gain;
+                for city in endpoints {
+                    active.push(city);
+                }
+                break;
+            }
+        }
+    }
+    total_gain
+}
+
+impl Solution {
+    /// The tour improved by [`two_opt`] until it is 2-optimal or `deadline` passes.
+    pub fn two_opt(&self, neighbor_lists: &[Vec<u32>], deadline: Option<Instant>) -> Solution {
+        self.improve_with(&[LocalSearch::TwoOpt], neighbor_lists, deadline)
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use crate::{
+    cover_tree::Distance,
+    format::TourFile,
+    json::JsonSolution,
+    problem::{City, Problem},
+};
+
+/// Relative difference allowed between the reported and the recomputed tour length.
+/// The solvers accumulate the length in `f32`, so exact equality is too strict.
+pub const DEFAULT_RELATIVE_TOLERANCE: f64 = 1e-5;
+
+/// A single problem found in a tour file. Line numbers refer to the tour file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TourIssue {
+    /// The tour has no city lines at all while the instance has cities.
+    Empty,
+    /// The last city line does not repeat the first one.
+    NotClosed {
+        first_line: usize,
+        last_line: usize,
+    },
+    /// The city index does not exist in the instance.
+    IndexOutOfRange {
+        line: usize,
+        index: u32,
+    },
+    /// The city does not exist in the instance.
+    UnknownCity {
+        line: usize,
+        city: City,
+    },
+    /// The city was already visited, as many times as it occurs in the instance.
+    RepeatedCity {
+        line: usize,
+        city: City,
+        first_line: usize,
+    },
+    /// The instance city at `index` is never visited.
+    MissingCity {
+        index: u32,
+        city: City,
+    },
+    DistanceMismatch {
+        reported: f64,
+        actual: f64,
+    },
+}
+
+impl std::fmt::Display for TourIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TourIssue::Empty => write!(f, "tour lists no cities"),
+            TourIssue::NotClosed {
+                first_line,
+                last_line,
+            } => write!(
+                f,
+                "line {}: tour does not return to the first city on line {}",
+                last_line, first_line
+            ),
+            TourIssue::IndexOutOfRange { line, index } => {
+                write!(f, "line {}: city index {} is out of range", line, index)
+            }
+            TourIssue::UnknownCity { line, city } => {
+                write!(f, "line {}: city {} is not in the instance", line, city)
+            }
+            TourIssue::RepeatedCity {
+                line,
+                city,
+                first_line,
+            } => write!(
+                f,
+                "line {}: city {} was already visited on line {}",
+                line, city, first_line
+            ),
+            TourIssue::MissingCity { index, city } => {
+                write!(f, "city {} at index {} is never visited", city, index)
+            }
+            TourIssue::DistanceMismatch { reported, actual } => write!(
+                f,
+                "reported distance {} differs from the actual tour length {} by {}",
+                reported,
+                actual,
+                (reported - actual).abs()
+            ),
+        }
+    }
+}
+
+pub struct VerificationReport {
+    pub issues: Vec<TourIssue>,
+    /// The length of the tour as listed in the file, closing edge included.
+    pub actual_distance: f64,
+    pub reported_distance: f64,
+    /// The visiting order as instance indices, if every city was matched exactly once.
+    pub order: Option<Vec<u32>>,
+}
+
+impl VerificationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks a tour file against `problem` and collects every issue rather than stopping
+/// at the first one. Cities are matched by coordinates; an instance that lists the same
+/// coordinates `k` times expects them `k` times in the tour.
+pub fn verify_tour(
+    problem: &Problem,
+    tour: &TourFile,
+    relative_tolerance: f64,
+) -> VerificationReport {
+    let mut issues = Vec::new();
+    let reported_distance = tour.total_distance as f64;
+    let mut visits = tour.cities.as_slice();
+    if problem.cities.is_empty() && visits.is_empty() {
+        return VerificationReport {
+            issues,
+            actual_distance: 0.0,
+            reported_distance,
+            order: Some(Vec::new()),
+        };
+    }
+    match (visits.first(), visits.last()) {
+        (Some(first), Some(last)) if visits.len() > 1 && first.1 == last.1 => {
+            visits = &visits[..visits.len() - 1];
+        }
+        (Some(first), Some(last)) => issues.push(TourIssue::NotClosed {
+            first_line: first.0,
+            last_line: last.0,
+        }),
+        _ => issues.push(TourIssue::Empty),
+    }
+
+    let actual_distance = visits
+        .iter()
+        .zip(visits.iter().cycle().skip(1))
+        .map(|((_, a), (_, b))| a.distance(b) as f64)
+        .sum::<f64>();
+
+    // indices are popped from the back, so store them in reverse to match in instance order
+    let mut unvisited: HashMap<City, Vec<u32>> = HashMap::new();
+    for (i, city) in problem.cities.iter().enumerate().rev() {
+        unvisited.entry(*city).or_default().push(i as u32);
+    }
+    let mut first_visit_line: HashMap<City, usize> = HashMap::new();
+    let mut order = Vec::with_capacity(visits.len());
+    for &(line, city) in visits {
+        let first_line = *first_visit_line.entry(city).or_insert(line);
+        match unvisited.get_mut(&city) {
+            None => issues.push(TourIssue::UnknownCity { line, city }),
+            Some(indices) => match indices.pop() {
+                Some(index) => order.push(index),
+                None => issues.push(TourIssue::RepeatedCity {
+                    line,
+                    city,
+                    first_line,
+                }),
+            },
+        }
+    }
+    let mut missing = unvisited
+        .into_iter()
+        .flat_map(|(city, indices)| indices.into_iter().map(move |index| (index, city)))
+        .collect::<Vec<_>>();
+    missing.sort_unstable();
+    issues.extend(
+        missing
+            .into_iter()
+            .map(|(index, city)| TourIssue::MissingCity { index, city }),
+    );
+
+    let tolerance = relative_tolerance * actual_distance.max(1.0);
+    if (reported_distance - actual_distance).abs() > tolerance {
+        issues.push(TourIssue::DistanceMismatch {
+            reported: reported_distance,
+            actual: actual_distance,
+        });
+    }
+
+    let order_is_complete = order.len() == problem.cities.len();
+    VerificationReport {
+        issues,
+        actual_distance,
+        reported_distance,
+        order: order_is_complete.then_some(order),
+    }
+}
+
+/// Checks a JSON result against `problem` like [`verify_tour`]. Its order holds instance
+/// indices without the closing city, so issues give 1-based positions in the order in
+/// place of line numbers.
+pub fn verify_json_solution(
+    problem: &Problem,
+    solution: &JsonSolution,
+    relative_tolerance: f64,
+) -> VerificationReport {
+    let mut out_of_range = Vec::new();
+    let mut cities = Vec::with_capacity(solution.order.len() + 1);
+    for (i, &index) in solution.order.iter().enumerate() {
+        match problem.cities.get(index as usize) {
+            Some(&city) => cities.push((i + 1, city)),
+            None => out_of_range.push(TourIssue::IndexOutOfRange { line: i + 1, index }),
+        }
+    }
+    if let Some(&(_, first)) = cities.first() {
+        cities.push((solution.order.len() + 1, first));
+    }
+    let tour = TourFile {
+        total_distance: solution.total_distance,
+        cities,
+    };
+    let mut report = verify_tour(problem, &tour, relative_tolerance);
+    if !out_of_range.is_empty() {
+        out_of_range.append(&mut report.issues);
+        report.issues = out_of_range;
+        report.order = None;
+    }
+    report
+}
@@ -0,0 +1,160 @@
+use std::{collections::HashMap, io::BufRead, rc::Rc};
+
+use crate::{
+    format::{FormatError, TourFile},
+    problem::{Problem, Solution},
+    verify::{TourIssue, verify_tour},
+};
+
+/// How a previously computed tour is stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TourFormat {
+    /// Decide from the content: the homework output format has a total distance line
+    /// followed by `x y z` lines, anything else is read as indices.
+    Auto,
+    /// The homework output format written by [`Solution::write_to`].
+    Coordinates,
+    /// One 0-based city index per line. The first index may be repeated at the end.
+    Indices,
+}
+
+#[derive(Debug)]
+pub enum WarmStartError {
+    Format(FormatError),
+    /// The tour could be read but does not visit every city of the problem exactly once.
+    InvalidTour(Vec<TourIssue>),
+}
+
+impl std::fmt::Display for WarmStartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WarmStartError::Format(e) => write!(f, "{}", e),
+            WarmStartError::InvalidTour(issues) => {
+                write!(f, "tour does not match the problem")?;
+                for issue in issues {
+                    write!(f, "\n  {}", issue)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for WarmStartError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WarmStartError::Format(e) => Some(e),
+            WarmStartError::InvalidTour(_) => None,
+        }
+    }
+}
+
+impl From<FormatError> for WarmStartError {
+    fn from(e: FormatError) -> Self {
+        WarmStartError::Format(e)
+    }
+}
+
+impl From<std::io::Error> for WarmStartError {
+    fn from(e: std::io::Error) -> Self {
+        WarmStartError::Format(FormatError::Io(e))
+    }
+}
+
+fn looks_like_coordinates(content: &str) -> bool {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    lines.next();
+    lines
+        .next()
+        .is_some_and(|line| line.split_whitespace().count() == 3)
+}
+
+/// Maps a tour stored as 0-based indices back to a [`Solution`] of `problem`.
+pub fn solution_from_indices(
+    problem: &Rc<Problem>,
+    reader: impl BufRead,
+) -> Result<Solution, WarmStartError> {
+    let num_cities = problem.cities.len();
+    let mut stops = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = i + 1;
+        let content = line.trim();
+        if content.is_empty() {
+            continue;
+        }
+        let index = content
+            .parse::<u32>()
+            .map_err(|_| FormatError::InvalidIndex {
+                line: line_number,
+                content: content.to_string(),
+            })?;
+        stops.push((line_number, index));
+    }
+    if stops.len() == num_cities + 1 && stops.first().map(|s| s.1) == stops.last().map(|s| s.1) {
+        stops.pop();
+    }
+
+    let mut issues = Vec::new();
+    let mut first_visit_line: HashMap<u32, usize> = HashMap::new();
+    for &(line, index) in &stops {
+        if index as usize >= num_cities {
+            issues.push(TourIssue::IndexOutOfRange { line, index });
+        } else if let Some(&first_line) = first_visit_line.get(&index) {
+            issues.push(TourIssue::RepeatedCity {
+                line,
+                city: problem.cities[index as usize],
+                first_line,
+            });
+        } else {
+            first_visit_line.insert(index, line);
+        }
+    }
+    issues.extend(
+        (0..num_cities as u32)
+            .filter(|index| !first_visit_line.contains_key(index))
+            .map(|index| TourIssue::MissingCity {
+                index,
+                city: problem.cities[index as usize],
+            }),
+    );
+    if !issues.is_empty() {
+        return Err(WarmStartError::InvalidTour(issues));
+    }
+    let order = stops.into_iter().map(|(_, index)| index).collect();
+    Ok(Solution::new(order, Rc::downgrade(problem), None))
+}
+
+/// Maps a tour in the homework output format back to a [`Solution`] of `problem`.
+/// The reported total distance is ignored, since the tour may come from another tool.
+pub fn solution_from_coordinates(
+    problem: &Rc<Problem>,
+    reader: impl BufRead,
+) -> Result<Solution, WarmStartError> {
+    let tour = TourFile::from_reader(reader)?;
+    let report = verify_tour(problem, &tour, f64::INFINITY);
+    match report.order {
+        Some(order) if report.is_valid() => Ok(Solution::new(order, Rc::downgrade(problem), None)),
+        _ => Err(WarmStartError::InvalidTour(report.issues)),
+    }
+}
+
+pub fn load_tour(
+    problem: &Rc<Problem>,
+    mut reader: impl BufRead,
+    format: TourFormat,
+) -> Result<Solution, WarmStartError> {
+    match format {
+        TourFormat::Coordinates => solution_from_coordinates(problem, reader),
+        TourFormat::Indices => solution_from_indices(problem, reader),
+        TourFormat::Auto => {
+            let mut content = String::new();
+            reader.read_to_string(&mut content)?;
+            if looks_like_coordinates(&content) {
+                solution_from_coordinates(problem, content.as_bytes())
+            } else {
+                solution_from_indices(problem, content.as_bytes())
+            }
+        }
+    }
+}
@@ -1,9 +1,3 @@
-use std::cell::RefCell;
-
-use rand::distr::Uniform;
-use rand::rngs::StdRng;
-use rand::seq::SliceRandom;
-use rand::{Rng, SeedableRng};
 use rust::cover_tree::{CoverTree, Distance};
 use rust::generator::ProblemGenerator;
 
@@ -21,7 +15,7 @@ fn pressure_test_cover_tree_nearest_neighbor() {
     let mut tree = CoverTree::new();
 
     for (i, city) in problem.cities.iter().enumerate() {
-        tree.insert(city.clone(), i as u32);
+        tree.insert(*city, i as u32);
         // if let Err(e) = tree.assert_valid_cover_tree() {
         //     println!("Cover tree failed validation after inserting: {}", e);
         //     tree.print();
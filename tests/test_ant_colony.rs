@@ -0,0 +1,90 @@
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rust::{
+    ant_colony::{AntColony, AntSystem},
+    generator::ProblemGenerator,
+    local_search::LocalSearch,
+    problem::{City, Problem, Solution},
+};
+
+const ANT_SYSTEMS: [AntSystem; 2] = [AntSystem::AntColonySystem, AntSystem::MaxMinAntSystem];
+
+/// A timer that started long enough ago to leave `budget` of a one-second limit.
+fn timer_leaving(budget: Duration) -> Instant {
+    Instant::now() - (Duration::from_secs(1) - budget)
+}
+
+#[test]
+fn test_ant_colony_improves_nearest_neighbor() {
+    let num_cities = 200;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 9).generate_problem());
+    let nearest_neighbor = Solution::from_nearest_neighbor(&problem, 0);
+    for ant_system in ANT_SYSTEMS {
+        for local_searches in [vec![], vec![LocalSearch::TwoOpt, LocalSearch::OrOpt]] {
+            let mut ant_colony = AntColony::new(Rc::clone(&problem), ant_system);
+            ant_colony.local_searches = local_searches.clone();
+            let timer = timer_leaving(Duration::from_millis(300));
+            let solution = ant_colony.solve(&timer, 1);
+            assert!(timer.elapsed() < Duration::from_millis(1200));
+            assert!(solution.is_valid(num_cities as u32));
+            // without local search the ants need longer to beat their reference tour
+            let bound = if local_searches.is_empty() { 1.0 } else { 0.95 };
+            assert!(
+                solution.total_distance() <= bound * nearest_neighbor.total_distance(),
+                "{:?} with {:?}: {} vs {}",
+                ant_system,
+                local_searches,
+                solution.total_distance(),
+                nearest_neighbor.total_distance()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_ant_colony_tiny_problems() {
+    for num_cities in 1..8 {
+        let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 4).generate_problem());
+        for ant_system in ANT_SYSTEMS {
+            let ant_colony = AntColony::new(Rc::clone(&problem), ant_system);
+            let solution = ant_colony.solve(&timer_leaving(Duration::from_millis(50)), 1);
+            assert!(solution.is_valid(num_cities as u32));
+        }
+    }
+}
+
+#[test]
+fn test_ant_colony_keeps_deadline() {
+    let num_cities = 2000;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 6).generate_problem());
+    for ant_system in ANT_SYSTEMS {
+        let timer = Instant::now();
+        let solution = AntColony::new(Rc::clone(&problem), ant_system).solve(&timer, 0);
+        assert!(solution.is_valid(num_cities as u32));
+        assert!(timer.elapsed() < Duration::from_millis(500));
+    }
+}
+
+/// `num_cities` cities on a few points, so that most of them are duplicates.
+fn duplicated_problem(num_cities: usize) -> Problem {
+    Problem::new(
+        (0..num_cities as u32)
+            .map(|i| City::new(i % 5 * 10, i % 3 * 10, 0))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_ant_colony_with_duplicate_cities() {
+    for num_cities in (3..90).step_by(7) {
+        let problem = Rc::new(duplicated_problem(num_cities));
+        for ant_system in ANT_SYSTEMS {
+            let ant_colony = AntColony::new(Rc::clone(&problem), ant_system);
+            let solution = ant_colony.solve(&timer_leaving(Duration::from_millis(20)), 1);
+            assert!(solution.is_valid(num_cities as u32));
+        }
+    }
+}
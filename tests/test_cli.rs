@@ -0,0 +1,194 @@
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+use rust::{format::TourFile, generator::ProblemGenerator};
+
+const INSTANCE: &str = "5\n0 0 0\n10 0 0\n10 10 0\n0 10 0\n5 5 5\n";
+
+fn rust() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_rust"))
+}
+
+/// Runs the binary with `args` and `stdin` as its input.
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = rust()
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // the binary may reject its arguments and exit before reading its input
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+/// A path in the temporary directory that no other test uses.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rust-cli-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn test_invalid_arguments_are_rejected() {
+    for args in [
+        vec!["solve", "--solver", "bogus"],
+        vec!["solve", "--crossover", "order:-1"],
+        vec!["solve", "--mutation", "swap"],
+        vec!["solve", "--time-limit", "soon"],
+        vec!["solve", "--rank-pressure", "2.5"],
+        vec!["solve", "--offspring-local-search-probability", "1.5"],
+        vec!["solve", "--min-edge-distance", "-0.1"],
+        vec!["validate", "--tolerance"],
+        vec!["bogus"],
+    ] {
+        let output = run(&args, "");
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty(), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_solver_flags_need_their_solver() {
+    for (flags, solver) in [
+        (vec!["--crossover", "order"], "genetic"),
+        (vec!["--replacement", "crowding"], "genetic"),
+        (
+            vec!["--solver", "iterated-local-search", "--selection", "rank"],
+            "genetic",
+        ),
+        (
+            vec!["--acceptance-threshold", "0.01"],
+            "iterated-local-search",
+        ),
+        (
+            vec!["--solver", "genetic", "--cooling-schedule", "adaptive"],
+            "simulated-annealing",
+        ),
+        (
+            vec!["--solver", "tabu-search", "--reheats", "2"],
+            "simulated-annealing",
+        ),
+    ] {
+        let args = [vec!["solve", "-i", "-", "-o", "-"], flags].concat();
+        let output = run(&args, INSTANCE);
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(output.stdout.is_empty(), "{:?}", args);
+        assert!(
+            String::from_utf8_lossy(&output.stderr)
+                .contains(&format!("only applies to --solver {}", solver)),
+            "{:?}",
+            args
+        );
+    }
+    for flags in [
+        vec!["--solver", "genetic", "--crossover", "order"],
+        vec![
+            "--solver",
+            "iterated-local-search",
+            "--acceptance-threshold",
+            "0.01",
+        ],
+        vec!["--solver", "simulated-annealing", "--reheats", "1"],
+    ] {
+        let args = [vec!["solve", "-i", "-", "-o", "-", "-t", "1"], flags].concat();
+        assert!(run(&args, INSTANCE).status.success(), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_solve_writes_only_the_tour_to_stdout() {
+    let output = run(
+        &[
+            "solve", "-i", "-", "-o", "-", "--solver", "genetic", "-t", "1",
+        ],
+        INSTANCE,
+    );
+    assert!(output.status.success());
+    let tour = TourFile::from_reader(output.stdout.as_slice()).unwrap();
+    // every city once, with the first one repeated at the end
+    assert_eq!(tour.cities.len(), 6);
+    assert_eq!(tour.cities[0].1, tour.cities[5].1);
+}
+
+#[test]
+fn test_solve_validate_round_trip() {
+    let problem = ProblemGenerator::new(30, 1000.0, 19).generate_problem();
+    let (input, tour) = (temp_path("input.txt"), temp_path("tour.txt"));
+    problem.write_to(fs::File::create(&input).unwrap()).unwrap();
+    let (input_arg, tour_arg) = (input.to_str().unwrap(), tour.to_str().unwrap());
+    let solved = run(&["solve", "-i", input_arg, "-o", tour_arg, "-t", "1"], "");
+    assert!(solved.status.success());
+    let validated = run(&["validate", "-i", input_arg, "-t", tour_arg], "");
+    assert!(validated.status.success());
+    assert!(String::from_utf8_lossy(&validated.stdout).starts_with("Valid tour of length"));
+    // a tour that misses a city is reported with its line
+    let broken = fs::read_to_string(&tour).unwrap();
+    let broken = broken.lines().take(10).collect::<Vec<_>>().join("\n");
+    fs::write(&tour, broken).unwrap();
+    let rejected = run(&["validate", "-i", input_arg, "-t", tour_arg], "");
+    assert!(!rejected.status.success());
+    assert!(String::from_utf8_lossy(&rejected.stdout).contains(tour_arg));
+    fs::remove_file(input).unwrap();
+    fs::remove_file(tour).unwrap();
+}
+
+#[test]
+fn test_solve_validate_json_round_trip() {
+    let input = temp_path("json-input.txt");
+    let tour = temp_path("tour.json");
+    fs::write(&input, INSTANCE).unwrap();
+    let (input_arg, tour_arg) = (input.to_str().unwrap(), tour.to_str().unwrap());
+    let solved = run(&["solve", "-i", input_arg, "-o", tour_arg, "-t", "1"], "");
+    assert!(solved.status.success());
+    let validated = run(&["validate", "-i", input_arg, "-t", tour_arg], "");
+    assert!(validated.status.success(), "{:?}", validated);
+    assert!(String::from_utf8_lossy(&validated.stdout).starts_with("Valid tour of length"));
+    fs::remove_file(input).unwrap();
+    fs::remove_file(tour).unwrap();
+}
+
+#[test]
+fn test_info() {
+    let input = temp_path("info.txt");
+    fs::write(&input, INSTANCE).unwrap();
+    let output = run(&["info", "-i", input.to_str().unwrap()], "");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Number of cities: 5\n"));
+    fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn test_info_with_duplicate_cities() {
+    let input = temp_path("duplicates.txt");
+    fs::write(&input, "4\n0 0 0\n3 4 0\n0 0 0\n3 4 0\n").unwrap();
+    let output = run(&["info", "-i", input.to_str().unwrap()], "");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Duplicate cities: 2\n"));
+    fs::remove_file(input).unwrap();
+}
+
+#[test]
+fn test_tournament_size_must_fit_the_population() {
+    for size in ["0", "201"] {
+        let args = [
+            "solve",
+            "-i",
+            "-",
+            "-o",
+            "-",
+            "--solver",
+            "genetic",
+            "--selection",
+            "tournament",
+            "--tournament-size",
+            size,
+        ];
+        let output = run(&args, INSTANCE);
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("--tournament-size"));
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rust::{
+    construction::{Construction, build_start_tour},
+    generator::ProblemGenerator,
+    problem::{City, Problem, Solution},
+};
+
+const CONSTRUCTIONS: [Construction; 9] = [
+    Construction::NearestNeighbor,
+    Construction::NearestInsertion,
+    Construction::CheapestInsertion,
+    Construction::FarthestInsertion,
+    Construction::GreedyEdge,
+    Construction::HilbertCurve,
+    Construction::MortonOrder,
+    Construction::Christofides,
+    Construction::Savings,
+];
+
+#[test]
+fn test_constructions_are_valid() {
+    let num_cities = 300;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 42).generate_problem());
+    for construction in CONSTRUCTIONS {
+        for start_index in [0, 17, num_cities - 1] {
+            let solution = construction.build(&problem, start_index);
+            assert!(solution.is_valid(num_cities as u32), "{:?}", construction);
+            assert_eq!(solution.order_without_loop[0], start_index as u32);
+            let recomputed = Solution::new(
+                solution.order_without_loop.clone(),
+                Rc::downgrade(&problem),
+                None,
+            );
+            assert!(
+                (solution.total_distance() - recomputed.total_distance()).abs()
+                    < 1e-5 * recomputed.total_distance(),
+                "{:?} reports a wrong length",
+                construction
+            );
+        }
+    }
+}
+
+#[test]
+fn test_constructions_on_tiny_problems() {
+    for num_cities in 1..4 {
+        let problem = Rc::new(Problem::new(
+            (0..num_cities).map(|i| City::new(i, i * i, 0)).collect(),
+        ));
+        for construction in CONSTRUCTIONS {
+            let solution = construction.build(&problem, 0);
+            assert!(solution.is_valid(num_cities));
+        }
+    }
+}
+
+#[test]
+fn test_constructions_with_duplicate_cities() {
+    for num_cities in 3..40 {
+        // every city appears twice, some of them three times
+        let problem = Rc::new(Problem::new(
+            (0..num_cities)
+                .map(|i| City::new(i / 2 % 7, i / 2 / 7, 0))
+                .chain((0..num_cities / 3).map(|i| City::new(i % 7, 0, 0)))
+                .collect(),
+        ));
+        let total = problem.cities.len() as u32;
+        for construction in CONSTRUCTIONS {
+            let solution = construction.build(&problem, 0);
+            assert!(solution.is_valid(total), "{:?}", construction);
+        }
+    }
+}
+
+#[test]
+fn test_cheapest_insertion_within_twice_optimum() {
+    let problem = Rc::new(ProblemGenerator::new(8, 1000.0, 3).generate_problem());
+    let optimum = Solution::from_brute_force(&problem).total_distance();
+    // cheapest insertion is a 2-approximation on metric instances
+    for start_index in 0..8 {
+        let solution = Solution::from_cheapest_insertion(&problem, start_index);
+        assert!(solution.total_distance() <= 2.0 * optimum);
+    }
+}
+
+#[test]
+fn test_hilbert_curve_on_grid_takes_unit_steps() {
+    let side = 8;
+    let mut cities = Vec::new();
+    for x in 0..side {
+        for y in 0..side {
+            for z in 0..side {
+                cities.push(City::new(x, y, z));
+            }
+        }
+    }
+    let problem = Rc::new(Problem::new(cities));
+    let solution = Solution::from_hilbert_curve(&problem, 0);
+    assert!(solution.is_valid(side * side * side));
+    // consecutive cells of a Hilbert curve are always face neighbors
+    for pair in solution.order_without_loop.windows(2) {
+        let a = problem.cities[pair[0] as usize];
+        let b = problem.cities[pair[1] as usize];
+        assert_eq!(a.x.abs_diff(b.x) + a.y.abs_diff(b.y) + a.z.abs_diff(b.z), 1);
+    }
+}
+
+#[test]
+fn test_christofides_within_one_and_a_half_optimum() {
+    for seed in 0..4 {
+        let problem = Rc::new(ProblemGenerator::new(8, 1000.0, seed).generate_problem());
+        let optimum = Solution::from_brute_force(&problem).total_distance();
+        let solution = Solution::from_christofides(&problem, 0);
+        assert!(solution.is_valid(8));
+        assert!(solution.total_distance() <= 1.5 * optimum * (1.0 + 1e-5));
+    }
+}
+
+#[test]
+fn test_savings_on_clustered_problem() {
+    let num_cities = 400;
+    let max_radius = 10000.0;
+    let generator = ProblemGenerator::new(num_cities, max_radius, 5);
+    let problem = Rc::new(generator.generate_clustered_problem(8, 500.0));
+    assert_eq!(problem.cities.len(), num_cities);
+    for city in &problem.cities {
+        assert!(
+            [city.x, city.y, city.z]
+                .iter()
+                .all(|&c| c as f32 <= 2.0 * max_radius)
+        );
+    }
+    let savings = Solution::from_savings(&problem, 0);
+    assert!(savings.is_valid(num_cities as u32));
+    let nearest_neighbor = Solution::from_nearest_neighbor(&problem, 0);
+    assert!(savings.total_distance() < 1.1 * nearest_neighbor.total_distance());
+}
+
+#[test]
+fn test_start_tour_falls_back_to_cheaper_constructions() {
+    let problem = Rc::new(ProblemGenerator::new(100_000, 100_000.0, 9).generate_problem());
+    let built_by = |time_left: Duration, construction: Construction| {
+        let deadline = Instant::now() + time_left;
+        let start_tour = build_start_tour(&problem, &[Construction::GreedyEdge], &[], deadline);
+        start_tour.order_without_loop == construction.build(&problem, 0).order_without_loop
+    };
+    assert!(built_by(
+        Duration::from_secs(3600),
+        Construction::GreedyEdge
+    ));
+    // greedy edge does not fit in half a second, nearest neighbor does
+    assert!(built_by(
+        Duration::from_secs(1),
+        Construction::NearestNeighbor
+    ));
+    assert!(built_by(Duration::ZERO, Construction::HilbertCurve));
+}
@@ -0,0 +1,113 @@
+use std::rc::Rc;
+
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+use rust::{
+    crossover::{
+        Crossover, CycleCrossover, EdgeAssemblyCrossover, EdgeRecombinationCrossover,
+        OrderCrossover, PartiallyMappedCrossover, PartitionCrossover, PreciseCrossover,
+        RandomCrossover, choose_crossover,
+    },
+    generator::ProblemGenerator,
+    local_search::LOCAL_SEARCH_NEIGHBORS,
+    neighbors::nearest_neighbor_lists,
+    problem::Solution,
+};
+
+fn crossovers() -> Vec<Box<dyn Crossover>> {
+    vec![
+        Box::new(RandomCrossover),
+        Box::new(PreciseCrossover),
+        Box::new(EdgeAssemblyCrossover),
+        Box::new(PartitionCrossover),
+        Box::new(OrderCrossover),
+        Box::new(PartiallyMappedCrossover),
+        Box::new(CycleCrossover),
+        Box::new(EdgeRecombinationCrossover),
+    ]
+}
+
+#[test]
+fn test_crossover_children_are_tours() {
+    let num_cities = 40;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 12).generate_problem());
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let mut rng = StdRng::seed_from_u64(3);
+    for crossover in crossovers() {
+        for _ in 0..20 {
+            let mut order1 = (0..num_cities as u32).collect::<Vec<_>>();
+            order1.shuffle(&mut rng);
+            // the second parent shares some edges with the first, like parents in a population
+            let mut order2 = order1.clone();
+            order2[5..15].reverse();
+            order2[20..35].shuffle(&mut rng);
+            let parent1 = Solution::new(order1, Rc::downgrade(&problem), None);
+            let parent2 = Solution::new(order2, Rc::downgrade(&problem), None);
+            crossover.crossover(
+                &parent1,
+                &parent2,
+                &neighbor_lists,
+                &mut rng,
+                &mut |child| {
+                    assert!(child.is_valid(num_cities as u32), "{}", crossover.name());
+                    true
+                },
+            );
+        }
+    }
+}
+
+#[test]
+fn test_cycle_crossover_keeps_positions() {
+    let problem = Rc::new(ProblemGenerator::new(30, 1000.0, 13).generate_problem());
+    let mut rng = StdRng::seed_from_u64(4);
+    let mut order1 = (0..30).collect::<Vec<u32>>();
+    order1.shuffle(&mut rng);
+    let mut order2 = order1.clone();
+    order2.shuffle(&mut rng);
+    let parent1 = Solution::new(order1.clone(), Rc::downgrade(&problem), None);
+    let parent2 = Solution::new(order2.clone(), Rc::downgrade(&problem), None);
+    let mut num_children = 0;
+    CycleCrossover.crossover(&parent1, &parent2, &[], &mut rng, &mut |child| {
+        for (i, &city) in child.order_without_loop.iter().enumerate() {
+            assert!(city == order1[i] || city == order2[i]);
+        }
+        num_children += 1;
+        true
+    });
+    assert_eq!(num_children, 2);
+}
+
+#[test]
+fn test_edge_recombination_of_equal_tours() {
+    let problem = Rc::new(ProblemGenerator::new(30, 1000.0, 14).generate_problem());
+    let parent = Solution::from_nearest_neighbor(&problem, 0);
+    let mut rng = StdRng::seed_from_u64(5);
+    let mut children = Vec::new();
+    EdgeRecombinationCrossover.crossover(&parent, &parent, &[], &mut rng, &mut |child| {
+        children.push(child.clone());
+        true
+    });
+    // with only the parent's edges to choose from, the child is the parent's cycle
+    assert_eq!(children.len(), 1);
+    assert!((children[0].total_distance() - parent.total_distance()).abs() < 1e-2);
+}
+
+#[test]
+fn test_crossover_mix_follows_the_weights() {
+    let mix: Vec<(Box<dyn Crossover>, f64)> = vec![
+        (Box::new(OrderCrossover), 3.0),
+        (Box::new(CycleCrossover), 1.0),
+        (Box::new(EdgeRecombinationCrossover), 0.0),
+    ];
+    let mut rng = StdRng::seed_from_u64(15);
+    let draws = 4000;
+    let order_draws = (0..draws)
+        .map(|_| choose_crossover(&mix, &mut rng).name())
+        .filter(|&name| {
+            assert_ne!(name, EdgeRecombinationCrossover.name());
+            name == OrderCrossover.name()
+        })
+        .count();
+    // three quarters of the draws, within five standard deviations of about 27
+    assert!((2860..3140).contains(&order_draws), "{}", order_draws);
+}
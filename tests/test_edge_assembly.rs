@@ -0,0 +1,84 @@
+use std::rc::Rc;
+
+use rand::{SeedableRng, rngs::StdRng};
+use rust::{
+    edge_assembly::{EAX_MAX_CHILDREN, edge_assembly_crossover},
+    generator::ProblemGenerator,
+    local_search::{LOCAL_SEARCH_NEIGHBORS, LocalSearch},
+    neighbors::nearest_neighbor_lists,
+    problem::Solution,
+};
+
+#[test]
+fn test_edge_assembly_crossover_children_are_tours() {
+    let num_cities = 200;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 3).generate_problem());
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let local_searches = [LocalSearch::TwoOpt, LocalSearch::OrOpt];
+    let mut rng = StdRng::seed_from_u64(1);
+    for start in 0..10 {
+        let parent1 = Solution::from_nearest_neighbor(&problem, start).improve_with(
+            &local_searches,
+            &neighbor_lists,
+            None,
+        );
+        let parent2 = Solution::from_nearest_neighbor(&problem, start + 10).improve_with(
+            &local_searches,
+            &neighbor_lists,
+            None,
+        );
+        let mut children = Vec::new();
+        edge_assembly_crossover(
+            &parent1,
+            &parent2,
+            &neighbor_lists,
+            &mut rng,
+            &mut |child| {
+                assert!(child.is_valid(num_cities as u32));
+                children.push(child.total_distance());
+                true
+            },
+        );
+        assert!(!children.is_empty() && children.len() <= EAX_MAX_CHILDREN);
+        // an AB-cycle that shortens the first parent gives a child shorter than both
+        let best_child = children.iter().cloned().fold(f32::INFINITY, f32::min);
+        assert!(
+            best_child < parent1.total_distance().max(parent2.total_distance()),
+            "{} vs {} and {}",
+            best_child,
+            parent1.total_distance(),
+            parent2.total_distance()
+        );
+    }
+}
+
+#[test]
+fn test_edge_assembly_crossover_of_equal_tours() {
+    let problem = Rc::new(ProblemGenerator::new(50, 1000.0, 4).generate_problem());
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let parent1 = Solution::from_nearest_neighbor(&problem, 0);
+    // the same cycle, starting elsewhere and running the other way
+    let mut order = parent1.order_without_loop.clone();
+    order.rotate_left(17);
+    order.reverse();
+    let parent2 = Solution::new(order, Rc::downgrade(&problem), None);
+    let mut rng = StdRng::seed_from_u64(1);
+    edge_assembly_crossover(&parent1, &parent2, &neighbor_lists, &mut rng, &mut |_| {
+        panic!("equal tours have no AB-cycles")
+    });
+}
+
+#[test]
+fn test_edge_assembly_crossover_stops_when_asked() {
+    let problem = Rc::new(ProblemGenerator::new(100, 1000.0, 5).generate_problem());
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let parent1 = Solution::from_nearest_neighbor(&problem, 0);
+    let parent2 = Solution::from_greedy_edge(&problem, 0);
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut calls = 0;
+    edge_assembly_crossover(&parent1, &parent2, &neighbor_lists, &mut rng, &mut |_| {
+        calls += 1;
+        false
+    });
+    assert_eq!(calls, 1);
+}
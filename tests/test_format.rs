@@ -0,0 +1,93 @@
+use std::rc::Rc;
+
+use rust::{
+    cover_tree::Distance,
+    format::FormatError,
+    problem::{City, Problem, Solution},
+};
+
+#[test]
+fn test_problem_round_trip() {
+    let input = "3\n1 2 3\n4 5 6\n\n7 8 9\n";
+    let problem = Problem::from_reader(input.as_bytes()).unwrap();
+    assert_eq!(
+        problem.cities,
+        vec![City::new(1, 2, 3), City::new(4, 5, 6), City::new(7, 8, 9)]
+    );
+    let mut output = Vec::new();
+    problem.write_to(&mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "3\n1 2 3\n4 5 6\n7 8 9\n"
+    );
+}
+
+#[test]
+fn test_distance_between_far_apart_cities() {
+    // any u32 coordinate parses, so the squared differences must not overflow
+    let input = "2\n0 0 0\n100000 0 4000000000\n";
+    let problem = Problem::from_reader(input.as_bytes()).unwrap();
+    let distance = problem.cities[0].distance(&problem.cities[1]);
+    assert_eq!(distance, 100000f32.hypot(4e9));
+}
+
+fn parse_error(input: &str) -> FormatError {
+    Problem::from_reader(input.as_bytes())
+        .err()
+        .expect("malformed input should not parse")
+}
+
+#[test]
+fn test_problem_parse_errors() {
+    assert!(matches!(parse_error(""), FormatError::MissingCityCount));
+    assert!(matches!(
+        parse_error("three\n"),
+        FormatError::InvalidCityCount { line: 1, .. }
+    ));
+    assert!(matches!(
+        parse_error("2\n1 2 3\n4 5\n"),
+        FormatError::WrongFieldCount { line: 3, found: 2 }
+    ));
+    assert!(matches!(
+        parse_error("1\n1 -2 3\n"),
+        FormatError::InvalidCoordinate {
+            line: 2,
+            field: 2,
+            ..
+        }
+    ));
+    assert!(matches!(
+        parse_error("3\n1 2 3\n"),
+        FormatError::MissingCities {
+            expected: 3,
+            found: 1
+        }
+    ));
+    assert!(matches!(
+        parse_error("4000000000\n"),
+        FormatError::MissingCities {
+            expected: 4000000000,
+            found: 0
+        }
+    ));
+    assert!(matches!(
+        parse_error("1\n1 2 3\n4 5 6\n"),
+        FormatError::TrailingContent { line: 3 }
+    ));
+}
+
+#[test]
+fn test_solution_write_to() {
+    let problem = Rc::new(Problem::new(vec![
+        City::new(0, 0, 0),
+        City::new(3, 4, 0),
+        City::new(3, 0, 0),
+    ]));
+    let solution = Solution::new(vec![1, 2, 0], Rc::downgrade(&problem), None);
+    let mut output = Vec::new();
+    solution.write_to(&mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "12\n3 4 0\n3 0 0\n0 0 0\n3 4 0\n"
+    );
+}
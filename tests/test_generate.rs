@@ -1,5 +1,5 @@
 use rust::generator::ProblemGenerator;
-use std::fs::{File, OpenOptions};
+use std::fs::OpenOptions;
 use std::io::Write;
 
 #[test]
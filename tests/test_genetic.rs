@@ -0,0 +1,96 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rand::rngs::StdRng;
+
+use rust::{
+    construction::Construction,
+    generator::ProblemGenerator,
+    genetic::GeneticAlgorithm,
+    local_search::{LOCAL_SEARCH_NEIGHBORS, LocalSearch},
+    neighbors::nearest_neighbor_lists,
+    problem::Solution,
+    replacement::{ElitistReplacement, Offspring, ReplacementStrategy},
+};
+
+/// A timer that started long enough ago to leave `budget` of a one-second limit.
+fn timer_leaving(budget: Duration) -> Instant {
+    Instant::now() - (Duration::from_secs(1) - budget)
+}
+
+/// Elitist replacement that first records, for every child it is offered, whether 2-opt
+/// can still shorten it.
+struct TwoOptCheckingReplacement {
+    neighbor_lists: Vec<Vec<u32>>,
+    two_opt_optimal: Rc<RefCell<Vec<bool>>>,
+}
+
+impl ReplacementStrategy for TwoOptCheckingReplacement {
+    fn survivors(
+        &self,
+        population: Vec<Rc<Solution>>,
+        offspring: Vec<Offspring>,
+        target_size: usize,
+        deadline: Option<Instant>,
+        rng: &mut StdRng,
+    ) -> Vec<Rc<Solution>> {
+        for Offspring { solution, .. } in &offspring {
+            let improved =
+                solution.improve_with(&[LocalSearch::TwoOpt], &self.neighbor_lists, None);
+            self.two_opt_optimal
+                .borrow_mut()
+                .push(improved.total_distance() == solution.total_distance());
+        }
+        ElitistReplacement { elite_count: None }.survivors(
+            population,
+            offspring,
+            target_size,
+            deadline,
+            rng,
+        )
+    }
+
+    fn name(&self) -> &'static str {
+        "2-opt checking"
+    }
+}
+
+/// Whether each child that reached the replacement was a 2-opt local optimum.
+fn children_two_opt_optimal(offspring_local_searches: Vec<LocalSearch>) -> Vec<bool> {
+    let problem = Rc::new(ProblemGenerator::new(50, 1000.0, 11).generate_problem());
+    let two_opt_optimal = Rc::new(RefCell::new(Vec::new()));
+    let mut genetic_algorithm = GeneticAlgorithm::new(Rc::clone(&problem), 20, 20);
+    genetic_algorithm.offspring_local_searches = offspring_local_searches;
+    genetic_algorithm.replacement = Box::new(TwoOptCheckingReplacement {
+        neighbor_lists: nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS),
+        two_opt_optimal: Rc::clone(&two_opt_optimal),
+    });
+    genetic_algorithm.solve(&timer_leaving(Duration::from_millis(300)), 1);
+    two_opt_optimal.take()
+}
+
+#[test]
+fn test_memetic_genetic_algorithm_improves_every_child() {
+    let memetic = children_two_opt_optimal(vec![LocalSearch::TwoOpt]);
+    assert!(!memetic.is_empty());
+    assert!(memetic.iter().all(|&optimal| optimal));
+    // without the offspring local search the crossovers leave improvable children
+    let plain = children_two_opt_optimal(Vec::new());
+    assert!(plain.iter().any(|&optimal| !optimal));
+}
+
+#[test]
+fn test_genetic_algorithm_with_start_independent_construction() {
+    let num_cities = 30;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 16).generate_problem());
+    let mut genetic_algorithm = GeneticAlgorithm::new(Rc::clone(&problem), 20, 20);
+    // greedy edge builds a single tour, so the population needs other tours next to it
+    genetic_algorithm.constructions = vec![Construction::GreedyEdge];
+    let greedy = Construction::GreedyEdge.build(&problem, 0);
+    let solution = genetic_algorithm.solve(&timer_leaving(Duration::from_millis(200)), 1);
+    assert!(solution.is_valid(num_cities as u32));
+    assert!(solution.total_distance() <= greedy.total_distance());
+}
@@ -0,0 +1,62 @@
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rust::{
+    generator::ProblemGenerator,
+    iterated_local_search::{Acceptance, IteratedLocalSearch},
+    local_search::{LOCAL_SEARCH_NEIGHBORS, LocalSearch},
+    neighbors::nearest_neighbor_lists,
+    problem::Solution,
+};
+
+/// A timer that started long enough ago to leave `budget` of a one-second limit.
+fn timer_leaving(budget: Duration) -> Instant {
+    Instant::now() - (Duration::from_secs(1) - budget)
+}
+
+#[test]
+fn test_iterated_local_search_beats_local_optimum() {
+    let num_cities = 300;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 5).generate_problem());
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let local_optimum = Solution::from_greedy_edge(&problem, 0).improve_with(
+        &[LocalSearch::TwoOpt, LocalSearch::OrOpt],
+        &neighbor_lists,
+        None,
+    );
+    for acceptance in [Acceptance::BetterOrEqual, Acceptance::Threshold(0.002)] {
+        let mut iterated_local_search = IteratedLocalSearch::new(Rc::clone(&problem));
+        iterated_local_search.acceptance = acceptance;
+        let solution = iterated_local_search.solve(&timer_leaving(Duration::from_millis(300)), 1);
+        assert!(solution.is_valid(num_cities as u32), "{:?}", acceptance);
+        assert_eq!(solution.order_without_loop[0], 0);
+        assert!(
+            solution.total_distance() < 0.98 * local_optimum.total_distance(),
+            "{:?}: {} vs {}",
+            acceptance,
+            solution.total_distance(),
+            local_optimum.total_distance()
+        );
+    }
+}
+
+#[test]
+fn test_iterated_local_search_keeps_deadline() {
+    let num_cities = 2000;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 6).generate_problem());
+    let timer = Instant::now();
+    let solution = IteratedLocalSearch::new(problem).solve(&timer, 0);
+    assert!(solution.is_valid(num_cities as u32));
+    assert!(timer.elapsed() < Duration::from_millis(500));
+}
+
+#[test]
+fn test_iterated_local_search_tiny_problems() {
+    for num_cities in 1..10 {
+        let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 2).generate_problem());
+        let solution = IteratedLocalSearch::new(problem).solve(&timer_leaving(Duration::ZERO), 1);
+        assert!(solution.is_valid(num_cities as u32));
+    }
+}
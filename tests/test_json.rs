@@ -0,0 +1,65 @@
+use std::rc::Rc;
+
+use rust::{
+    format::FormatError,
+    json::{JsonProblem, JsonSolution},
+    problem::{City, Problem, Solution},
+};
+
+#[test]
+fn test_json_problem_round_trip() {
+    let input = r#"{"cities": [
+        {"id": "a", "name": "Alpha", "x": 0, "y": 0, "z": 0},
+        {"x": 3, "y": 4, "z": 0}
+    ]}"#;
+    let json_problem = JsonProblem::from_reader(input.as_bytes()).unwrap();
+    assert_eq!(json_problem.cities[0].id.as_deref(), Some("a"));
+    assert_eq!(json_problem.cities[0].name.as_deref(), Some("Alpha"));
+    assert_eq!(json_problem.cities[1].id, None);
+    let problem = json_problem.to_problem();
+    assert_eq!(problem.cities, vec![City::new(0, 0, 0), City::new(3, 4, 0)]);
+
+    let mut written = Vec::new();
+    json_problem.write_to(&mut written).unwrap();
+    assert_eq!(
+        JsonProblem::from_reader(written.as_slice()).unwrap(),
+        json_problem
+    );
+}
+
+#[test]
+fn test_json_problem_rejects_negative_coordinates() {
+    let input = r#"{"cities": [{"x": 0, "y": -1, "z": 0}]}"#;
+    let Err(FormatError::Json(e)) = JsonProblem::from_reader(input.as_bytes()) else {
+        panic!("negative coordinates should be rejected");
+    };
+    assert_eq!(e.line(), 1);
+}
+
+#[test]
+fn test_json_solution_round_trip() {
+    let problem = Rc::new(Problem::new(vec![
+        City::new(0, 0, 0),
+        City::new(3, 0, 0),
+        City::new(3, 4, 0),
+    ]));
+    let solution = Solution::new(vec![2, 0, 1], Rc::downgrade(&problem), None);
+    let mut json_solution = JsonSolution::new(&solution);
+    json_solution.solver = Some("genetic".to_string());
+    json_solution.seed = Some(7);
+    assert_eq!(json_solution.total_distance, 12.0);
+
+    let mut written = Vec::new();
+    json_solution.write_to(&mut written).unwrap();
+    let text = String::from_utf8(written).unwrap();
+    assert!(
+        !text.contains("gap"),
+        "unknown fields are omitted: {}",
+        text
+    );
+    let read_back = JsonSolution::from_reader(text.as_bytes()).unwrap();
+    assert_eq!(read_back, json_solution);
+    let solution = read_back.to_solution(&problem);
+    assert_eq!(solution.order_without_loop, vec![2, 0, 1]);
+    assert_eq!(solution.total_distance(), 12.0);
+}
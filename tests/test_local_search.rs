@@ -0,0 +1,194 @@
+use std::{rc::Rc, time::Instant};
+
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use rust::{
+    cover_tree::Distance,
+    generator::ProblemGenerator,
+    local_search::{LOCAL_SEARCH_NEIGHBORS, LocalSearch},
+    neighbors::nearest_neighbor_lists,
+    problem::{City, Problem, Solution},
+    tour::ArrayTour,
+};
+
+const LOCAL_SEARCHES: [LocalSearch; 4] = [
+    LocalSearch::TwoOpt,
+    LocalSearch::OrOpt,
+    LocalSearch::ThreeOpt,
+    LocalSearch::LinKernighan,
+];
+
+fn recomputed_distance(solution: &Solution) -> f32 {
+    Solution::new(
+        solution.order_without_loop.clone(),
+        solution.problem.clone(),
+        None,
+    )
+    .total_distance()
+}
+
+#[test]
+fn test_local_searches_improve_and_keep_length() {
+    let num_cities = 500;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 42).generate_problem());
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let start = Solution::from_hilbert_curve(&problem, 7);
+    for local_search in LOCAL_SEARCHES {
+        let improved = start.improve_with(&[local_search], &neighbor_lists, None);
+        assert!(improved.is_valid(num_cities as u32), "{:?}", local_search);
+        assert_eq!(improved.order_without_loop[0], 7);
+        assert!(improved.total_distance() < start.total_distance());
+        let recomputed = recomputed_distance(&improved);
+        assert!(
+            (improved.total_distance() - recomputed).abs() < 1e-5 * recomputed,
+            "{:?} reports a wrong length",
+            local_search
+        );
+    }
+}
+
+#[test]
+fn test_two_opt_reaches_local_optimum() {
+    let num_cities = 60;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 7).generate_problem());
+    // with every other city as a candidate the result must be 2-optimal
+    let neighbor_lists = nearest_neighbor_lists(&problem, num_cities - 1);
+    let solution = Solution::from_morton_order(&problem, 0).two_opt(&neighbor_lists, None);
+    let order = &solution.order_without_loop;
+    let distance = |i: usize, j: usize| {
+        problem.cities[order[i % num_cities] as usize]
+            .distance(&problem.cities[order[j % num_cities] as usize])
+    };
+    for i in 0..num_cities {
+        for j in i + 2..num_cities {
+            let gain =
+                distance(i, i + 1) + distance(j, j + 1) - distance(i, j) - distance(i + 1, j + 1);
+            assert!(gain < 1e-2, "improving move {} {} left", i, j);
+        }
+    }
+}
+
+#[test]
+fn test_local_search_respects_deadline_and_tiny_problems() {
+    let problem = Rc::new(ProblemGenerator::new(200, 1000.0, 1).generate_problem());
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let start = Solution::from_morton_order(&problem, 0);
+    let unchanged = start.improve_with(&LOCAL_SEARCHES, &neighbor_lists, Some(Instant::now()));
+    assert_eq!(unchanged.order_without_loop, start.order_without_loop);
+
+    for num_cities in 1..6 {
+        let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 2).generate_problem());
+        let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+        let start = Solution::from_nearest_neighbor(&problem, 0);
+        let improved = start.improve_with(&LOCAL_SEARCHES, &neighbor_lists, None);
+        assert!(improved.is_valid(num_cities as u32));
+        assert!(improved.total_distance() <= start.total_distance() + 1e-3);
+    }
+    let empty = Rc::new(Problem::new(Vec::new()));
+    let solution = Solution::new(Vec::new(), Rc::downgrade(&empty), None);
+    assert!(
+        solution
+            .improve_with(&LOCAL_SEARCHES, &[], None)
+            .order_without_loop
+            .is_empty()
+    );
+}
+
+/// Whether `tour` is the cycle `expected`, read in either direction.
+fn is_cycle(tour: &ArrayTour, expected: &[u32]) -> bool {
+    let order = tour.order_from(expected[0]);
+    let mut reversed = order.clone();
+    reversed[1..].reverse();
+    order == expected || reversed == expected
+}
+
+#[test]
+fn test_swap_segments_in_both_directions() {
+    // p = 0, X = 1..2, Y = 3..5, q = 6
+    let expected = [
+        ([false, false], [0, 3, 4, 5, 1, 2, 6, 7]),
+        ([true, false], [0, 3, 4, 5, 2, 1, 6, 7]),
+        ([false, true], [0, 5, 4, 3, 1, 2, 6, 7]),
+        ([true, true], [0, 5, 4, 3, 2, 1, 6, 7]),
+    ];
+    for ([reverse_x, reverse_y], order) in expected {
+        // the same move on the tour stored in both directions
+        for stored in [(0..8).collect::<Vec<_>>(), (0..8).rev().collect()] {
+            let mut tour = ArrayTour::new(stored);
+            tour.swap_segments(0, (1, 2), (3, 5), 6, reverse_x, reverse_y);
+            assert!(is_cycle(&tour, &order), "{:?}", tour.order());
+        }
+    }
+}
+
+#[test]
+fn test_or_opt_moves_lone_city_back() {
+    // city 4 belongs between 1 and 2 on the line
+    let problem = Rc::new(Problem::new(
+        [0, 1, 3, 4, 2, 5, 6, 7]
+            .map(|x| City::new(x * 10, 0, 0))
+            .to_vec(),
+    ));
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let solution = Solution::new((0..8).collect(), Rc::downgrade(&problem), None);
+    let improved = solution.or_opt(&neighbor_lists, None);
+    assert_eq!(improved.total_distance(), 140.0);
+}
+
+#[test]
+fn test_three_opt_finds_segment_exchanges() {
+    // every improving move on a random tour must keep the tour valid and the cached
+    // length exact, whichever reconnection it uses
+    let num_cities = 300;
+    for seed in 0..5 {
+        let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, seed).generate_problem());
+        let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+        let mut order = (0..num_cities as u32).collect::<Vec<_>>();
+        order.shuffle(&mut StdRng::seed_from_u64(seed));
+        let start = Solution::new(order, Rc::downgrade(&problem), None);
+        let improved = start.three_opt(&neighbor_lists, None);
+        assert!(improved.is_valid(num_cities as u32));
+        let recomputed = recomputed_distance(&improved);
+        assert!((improved.total_distance() - recomputed).abs() < 1e-5 * recomputed);
+        assert!(recomputed < 0.5 * start.total_distance());
+    }
+}
+
+#[test]
+fn test_lin_kernighan_beats_two_opt() {
+    let num_cities = 1000;
+    let (mut two_opt_total, mut lin_kernighan_total) = (0.0, 0.0);
+    for seed in 0..3 {
+        let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, seed).generate_problem());
+        let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+        let mut order = (0..num_cities as u32).collect::<Vec<_>>();
+        order.shuffle(&mut StdRng::seed_from_u64(seed));
+        let start = Solution::new(order, Rc::downgrade(&problem), None);
+        let improved = start.lin_kernighan(&neighbor_lists, None);
+        assert!(improved.is_valid(num_cities as u32));
+        let recomputed = recomputed_distance(&improved);
+        assert!((improved.total_distance() - recomputed).abs() < 1e-5 * recomputed);
+        lin_kernighan_total += recomputed;
+        two_opt_total += start.two_opt(&neighbor_lists, None).total_distance();
+    }
+    assert!(
+        lin_kernighan_total < 0.97 * two_opt_total,
+        "{} vs {}",
+        lin_kernighan_total,
+        two_opt_total
+    );
+}
+
+#[test]
+fn test_rollback_restores_checkpoint() {
+    let mut tour = ArrayTour::new((0..10).collect());
+    tour.checkpoint();
+    tour.flip(1, 2, 6, 7);
+    tour.swap_segments(1, (6, 5), (4, 2), 7, false, true);
+    assert!(!is_cycle(&tour, &(0..10).collect::<Vec<_>>()));
+    tour.rollback();
+    assert_eq!(tour.order(), (0..10).collect::<Vec<_>>());
+    for city in 0..10 {
+        assert_eq!(tour.position(city), city as usize);
+    }
+}
@@ -0,0 +1,109 @@
+use rand::{SeedableRng, rngs::StdRng};
+use rust::mutation::{Mutation, mutate_with_rates};
+
+const MUTATIONS: [Mutation; 5] = [
+    Mutation::Inversion,
+    Mutation::Swap,
+    Mutation::Insertion,
+    Mutation::Scramble,
+    Mutation::DoubleBridge,
+];
+
+/// How many edges of the cyclic tour `order` are not edges of `original`.
+fn new_edges(original: &[u32], order: &[u32]) -> usize {
+    let n = original.len();
+    let edge = |order: &[u32], i: usize| {
+        let (a, b) = (order[i], order[(i + 1) % n]);
+        (a.min(b), a.max(b))
+    };
+    let original_edges = (0..n).map(|i| edge(original, i)).collect::<Vec<_>>();
+    (0..n)
+        .filter(|&i| !original_edges.contains(&edge(order, i)))
+        .count()
+}
+
+#[test]
+fn test_mutations_keep_permutations() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for mutation in MUTATIONS {
+        for n in [4, 5, 30] {
+            for _ in 0..50 {
+                let original = (0..n).collect::<Vec<u32>>();
+                let mut order = original.clone();
+                mutation.mutate(&mut order, &mut rng);
+                let mut sorted = order.clone();
+                sorted.sort_unstable();
+                assert_eq!(sorted, original, "{:?}", mutation);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_mutations_change_few_edges() {
+    let mut rng = StdRng::seed_from_u64(8);
+    let original = (0..100).collect::<Vec<u32>>();
+    for (mutation, max_new_edges) in [
+        (Mutation::Inversion, 2),
+        (Mutation::Swap, 4),
+        (Mutation::Insertion, 3),
+        (Mutation::Scramble, 9),
+        (Mutation::DoubleBridge, 3),
+    ] {
+        for _ in 0..50 {
+            let mut order = original.clone();
+            mutation.mutate(&mut order, &mut rng);
+            assert!(
+                new_edges(&original, &order) <= max_new_edges,
+                "{:?}",
+                mutation
+            );
+        }
+    }
+}
+
+#[test]
+fn test_tiny_tours_stay_unchanged() {
+    let mut rng = StdRng::seed_from_u64(9);
+    for mutation in MUTATIONS {
+        let mut order = vec![2, 0, 1];
+        mutation.mutate(&mut order, &mut rng);
+        assert_eq!(order, vec![2, 0, 1]);
+    }
+}
+
+#[test]
+fn test_mutated_children_differ_from_their_clone() {
+    let mut rng = StdRng::seed_from_u64(10);
+    let original = (0..30).collect::<Vec<u32>>();
+    // a scramble may shuffle its segment back into place, every other mutation moves a city
+    for mutation in MUTATIONS.into_iter().filter(|&m| m != Mutation::Scramble) {
+        for _ in 0..50 {
+            let mutated = mutate_with_rates(&[(mutation, 1.0)], &original, &mut rng);
+            assert!(
+                mutated.is_some_and(|order| order != original),
+                "{:?}",
+                mutation
+            );
+        }
+    }
+}
+
+#[test]
+fn test_mutation_rates_are_the_share_of_changed_children() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let original = (0..30).collect::<Vec<u32>>();
+    let mutations = [(Mutation::Swap, 0.25), (Mutation::Inversion, 0.0)];
+    let mut mutated = 0;
+    for _ in 0..4000 {
+        if let Some(order) = mutate_with_rates(&mutations, &original, &mut rng) {
+            assert_eq!(
+                new_edges(&original, &order).min(4),
+                new_edges(&original, &order)
+            );
+            mutated += 1;
+        }
+    }
+    // a quarter of the children, within five standard deviations of about 27
+    assert!((860..1140).contains(&mutated), "{}", mutated);
+}
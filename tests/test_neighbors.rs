@@ -0,0 +1,98 @@
+use rust::{
+    cover_tree::{CoverTree, Distance},
+    generator::ProblemGenerator,
+    neighbors::{Grid, UnvisitedCities, nearest_neighbor_lists},
+    problem::{City, Problem},
+};
+
+#[test]
+fn test_k_nearest_neighbors_matches_brute_force() {
+    let k = 8;
+    let problem = ProblemGenerator::new(2000, 1000.0, 42).generate_problem();
+    let mut tree = CoverTree::new();
+    for (i, city) in problem.cities.iter().enumerate() {
+        tree.insert(*city, i as u32);
+    }
+    for query in problem.cities.iter().take(200) {
+        let mut brute_distances = problem
+            .cities
+            .iter()
+            .map(|city| city.distance(query))
+            .collect::<Vec<_>>();
+        brute_distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let tree_distances = tree
+            .k_nearest_neighbors(query, k)
+            .into_iter()
+            .map(|(_, _, distance)| distance)
+            .collect::<Vec<_>>();
+        assert_eq!(tree_distances, brute_distances[..k]);
+    }
+}
+
+#[test]
+fn test_nearest_neighbor_lists_with_duplicate_cities() {
+    let problem = Problem::new(vec![
+        City::new(0, 0, 0),
+        City::new(10, 0, 0),
+        City::new(0, 0, 0),
+        City::new(3, 0, 0),
+    ]);
+    let lists = nearest_neighbor_lists(&problem, 2);
+    assert_eq!(lists, vec![vec![2, 3], vec![3, 0], vec![0, 3], vec![0, 2]]);
+}
+
+#[test]
+fn test_nearest_neighbor_lists_match_brute_force() {
+    let k = 8;
+    let generator = ProblemGenerator::new(600, 10000.0, 3);
+    for problem in [
+        generator.generate_problem(),
+        generator.generate_clustered_problem(5, 300.0),
+    ] {
+        let lists = nearest_neighbor_lists(&problem, k);
+        for (i, city) in problem.cities.iter().enumerate() {
+            let mut brute_force = (0..problem.cities.len() as u32)
+                .filter(|&j| j as usize != i)
+                .map(|j| (city.distance(&problem.cities[j as usize]), j))
+                .collect::<Vec<_>>();
+            brute_force.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let distances = lists[i]
+                .iter()
+                .map(|&j| city.distance(&problem.cities[j as usize]))
+                .collect::<Vec<_>>();
+            let expected = brute_force[..k].iter().map(|&(d, _)| d).collect::<Vec<_>>();
+            assert_eq!(distances, expected);
+        }
+    }
+}
+
+#[test]
+fn test_nearest_unvisited_matches_brute_force() {
+    let generator = ProblemGenerator::new(500, 10000.0, 4);
+    for problem in [
+        generator.generate_problem(),
+        generator.generate_clustered_problem(4, 200.0),
+    ] {
+        let cities = &problem.cities;
+        let grid = Grid::new(cities);
+        let mut unvisited = UnvisitedCities::new(&grid, cities);
+        // a nearest neighbor tour, checked against a scan at every step
+        let mut city = 0;
+        unvisited.visit(city);
+        for _ in 1..cities.len() {
+            let expected = (0..cities.len() as u32)
+                .filter(|&other| !unvisited.is_visited(other))
+                .map(|other| cities[city as usize].distance(&cities[other as usize]))
+                .min_by(|a, b| a.total_cmp(b));
+            let next = unvisited.nearest(city).unwrap();
+            assert!(!unvisited.is_visited(next));
+            assert_eq!(
+                Some(cities[city as usize].distance(&cities[next as usize])),
+                expected
+            );
+            unvisited.visit(next);
+            city = next;
+        }
+        assert_eq!(unvisited.nearest(city), None);
+    }
+}
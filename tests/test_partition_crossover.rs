@@ -0,0 +1,66 @@
+use std::rc::Rc;
+
+use rust::{
+    generator::ProblemGenerator,
+    local_search::{LOCAL_SEARCH_NEIGHBORS, LocalSearch},
+    neighbors::nearest_neighbor_lists,
+    partition_crossover::partition_crossover,
+    problem::Solution,
+};
+
+#[test]
+fn test_partition_crossover_combines_improvements() {
+    let num_cities = 200;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 8).generate_problem());
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let tour = Solution::from_nearest_neighbor(&problem, 0).two_opt(&neighbor_lists, None);
+    // each parent spoils the tour in a different place by reversing a segment
+    let mut order1 = tour.order_without_loop.clone();
+    order1[10..20].reverse();
+    let mut order2 = tour.order_without_loop.clone();
+    order2[100..110].reverse();
+    let parent1 = Solution::new(order1, Rc::downgrade(&problem), None);
+    let parent2 = Solution::new(order2, Rc::downgrade(&problem), None);
+    let mut best_child = f32::INFINITY;
+    partition_crossover(&parent1, &parent2, &mut |child| {
+        assert!(child.is_valid(num_cities as u32));
+        best_child = best_child.min(child.total_distance());
+        true
+    });
+    assert!(
+        (best_child - tour.total_distance()).abs() < 1e-2,
+        "{} vs {}",
+        best_child,
+        tour.total_distance()
+    );
+}
+
+#[test]
+fn test_partition_crossover_children_improve_their_parent() {
+    let num_cities = 300;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 9).generate_problem());
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let local_searches = [LocalSearch::TwoOpt, LocalSearch::OrOpt];
+    let parents = (0..8)
+        .map(|start| {
+            Solution::from_nearest_neighbor(&problem, start * 30).improve_with(
+                &local_searches,
+                &neighbor_lists,
+                None,
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut num_children = 0;
+    for parent1 in &parents {
+        for parent2 in &parents {
+            let longer = parent1.total_distance().max(parent2.total_distance());
+            partition_crossover(parent1, parent2, &mut |child| {
+                assert!(child.is_valid(num_cities as u32));
+                assert!(child.total_distance() < longer);
+                num_children += 1;
+                true
+            });
+        }
+    }
+    assert!(num_children > 0);
+}
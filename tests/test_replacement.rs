@@ -0,0 +1,143 @@
+use std::{rc::Rc, time::Instant};
+
+use rand::{SeedableRng, rngs::StdRng};
+use rust::{
+    generator::ProblemGenerator,
+    problem::{Problem, Solution},
+    replacement::{
+        CrowdingReplacement, DiversityReplacement, ElitistReplacement, Offspring,
+        ReplacementStrategy, TruncationReplacement, edge_distance,
+    },
+};
+
+const NUM_CITIES: usize = 20;
+
+/// A tour with the given order and a made-up length, so that the tests choose which tours
+/// are shorter.
+fn tour(problem: &Rc<Problem>, order: Vec<u32>, length: f32) -> Rc<Solution> {
+    Rc::new(Solution::new(order, Rc::downgrade(problem), Some(length)))
+}
+
+/// The identity tour with the segment from `start` to `end` reversed, which differs from
+/// it in two edges.
+fn reversed(start: usize, end: usize) -> Vec<u32> {
+    let mut order = (0..NUM_CITIES as u32).collect::<Vec<_>>();
+    order[start..end].reverse();
+    order
+}
+
+fn lengths(solutions: &[Rc<Solution>]) -> Vec<f32> {
+    let mut lengths = solutions
+        .iter()
+        .map(|solution| solution.total_distance())
+        .collect::<Vec<_>>();
+    lengths.sort_by(f32::total_cmp);
+    lengths
+}
+
+fn orphans(children: Vec<Rc<Solution>>, parent: &Rc<Solution>) -> Vec<Offspring> {
+    children
+        .into_iter()
+        .map(|solution| Offspring {
+            solution,
+            parents: [parent.clone(), parent.clone()],
+        })
+        .collect()
+}
+
+#[test]
+fn test_edge_distance() {
+    let problem = Rc::new(ProblemGenerator::new(NUM_CITIES, 1000.0, 15).generate_problem());
+    let a = tour(&problem, reversed(0, 0), 1.0);
+    let b = tour(&problem, reversed(3, 8), 1.0);
+    let c = tour(&problem, reversed(10, 15), 1.0);
+    assert_eq!(edge_distance(&a, &a), 0);
+    assert_eq!(edge_distance(&a, &b), 2);
+    assert_eq!(edge_distance(&b, &c), edge_distance(&c, &b));
+    assert_eq!(edge_distance(&b, &c), 4);
+}
+
+#[test]
+fn test_truncation_and_elitist_keep_the_shortest() {
+    let problem = Rc::new(ProblemGenerator::new(NUM_CITIES, 1000.0, 16).generate_problem());
+    let population = (0..6)
+        .map(|i| tour(&problem, reversed(i, i + 5), 10.0 + i as f32))
+        .collect::<Vec<_>>();
+    let children = (0..4)
+        .map(|i| tour(&problem, reversed(i + 6, i + 12), 12.5 + i as f32))
+        .collect::<Vec<_>>();
+    let mut rng = StdRng::seed_from_u64(12);
+    let survivors = TruncationReplacement.survivors(
+        population.clone(),
+        orphans(children.clone(), &population[0]),
+        5,
+        None,
+        &mut rng,
+    );
+    assert_eq!(lengths(&survivors), vec![10.0, 11.0, 12.0, 12.5, 13.0]);
+    let elitist = ElitistReplacement {
+        elite_count: Some(3),
+    };
+    let survivors = elitist.survivors(
+        population.clone(),
+        orphans(children, &population[0]),
+        5,
+        None,
+        &mut rng,
+    );
+    assert_eq!(survivors.len(), 5);
+    assert_eq!(lengths(&survivors)[..3], [10.0, 11.0, 12.0]);
+}
+
+#[test]
+fn test_crowding_replaces_the_most_similar_parent() {
+    let problem = Rc::new(ProblemGenerator::new(NUM_CITIES, 1000.0, 17).generate_problem());
+    let parent1 = tour(&problem, reversed(0, 0), 10.0);
+    let parent2 = tour(&problem, reversed(2, 17), 20.0);
+    let population = vec![parent1.clone(), parent2.clone()];
+    // close to the first parent and shorter than it
+    let shorter = tour(&problem, reversed(4, 7), 9.0);
+    // close to the first parent but longer, although shorter than the second one
+    let longer = tour(&problem, reversed(8, 11), 15.0);
+    // close to the second parent and shorter than it, but longer than the other children
+    let mut order = reversed(2, 17);
+    order[5..8].reverse();
+    let near_second = tour(&problem, order, 16.0);
+    let offspring = [longer, shorter.clone(), near_second.clone()]
+        .into_iter()
+        .map(|solution| Offspring {
+            solution,
+            parents: [parent1.clone(), parent2.clone()],
+        })
+        .collect();
+    let mut rng = StdRng::seed_from_u64(13);
+    let survivors = CrowdingReplacement.survivors(population, offspring, 2, None, &mut rng);
+    assert!(Rc::ptr_eq(&survivors[0], &shorter));
+    assert!(Rc::ptr_eq(&survivors[1], &near_second));
+}
+
+#[test]
+fn test_diversity_skips_close_tours() {
+    let problem = Rc::new(ProblemGenerator::new(NUM_CITIES, 1000.0, 18).generate_problem());
+    let best = tour(&problem, reversed(0, 0), 10.0);
+    // two edges away from the best tour
+    let close = tour(&problem, reversed(3, 6), 11.0);
+    // four edges away from the best tour
+    let mut order = reversed(8, 12);
+    order[13..18].reverse();
+    let distant = tour(&problem, order, 12.0);
+    let longest = tour(&problem, reversed(1, 19), 13.0);
+    let population = vec![best.clone(), close.clone(), distant.clone(), longest];
+    let replacement = DiversityReplacement { min_distance: 0.15 };
+    let mut rng = StdRng::seed_from_u64(14);
+    let survivors = replacement.survivors(population.clone(), Vec::new(), 2, None, &mut rng);
+    assert!(Rc::ptr_eq(&survivors[0], &best));
+    assert!(Rc::ptr_eq(&survivors[1], &distant));
+    // with more places than distant tours, the close ones fill them
+    let survivors = replacement.survivors(population.clone(), Vec::new(), 3, None, &mut rng);
+    assert_eq!(lengths(&survivors), vec![10.0, 11.0, 12.0]);
+    // past the deadline the shortest tours survive without comparing them
+    let deadline = Some(Instant::now());
+    let survivors = replacement.survivors(population, Vec::new(), 2, deadline, &mut rng);
+    assert_eq!(lengths(&survivors), vec![10.0, 11.0]);
+}
@@ -0,0 +1,74 @@
+use rand::{SeedableRng, rngs::StdRng};
+use rust::selection::{
+    RankSelection, RouletteSelection, SelectionStrategy, StochasticUniversalSampling,
+    TournamentSelection,
+};
+
+const LENGTHS: [f32; 5] = [130.0, 100.0, 120.0, 110.0, 140.0];
+
+/// How often each tour is picked in `draws` samples.
+fn counts(selection: &dyn SelectionStrategy, draws: usize) -> Vec<usize> {
+    let mut rng = StdRng::seed_from_u64(10);
+    let mut sampler = selection.sampler(&LENGTHS);
+    let mut counts = vec![0; LENGTHS.len()];
+    for _ in 0..draws {
+        counts[sampler.sample(&mut rng)] += 1;
+    }
+    counts
+}
+
+#[test]
+fn test_selections_prefer_shorter_tours() {
+    let selections: [Box<dyn SelectionStrategy>; 4] = [
+        Box::new(RouletteSelection),
+        // tournaments of distinct tours, so any bigger one never picks the second longest
+        Box::new(TournamentSelection { size: 2 }),
+        Box::new(RankSelection { pressure: 1.5 }),
+        Box::new(StochasticUniversalSampling),
+    ];
+    for selection in selections {
+        let counts = counts(selection.as_ref(), 10000);
+        // the tours ordered from the shortest to the longest
+        let by_length = [1, 3, 2, 0, 4].map(|index| counts[index]);
+        assert!(
+            by_length.windows(2).all(|pair| pair[0] > pair[1]),
+            "{}: {:?}",
+            selection.name(),
+            counts
+        );
+    }
+}
+
+#[test]
+fn test_rank_selection_without_pressure_is_uniform() {
+    let counts = counts(&RankSelection { pressure: 1.0 }, 10000);
+    assert!(counts.iter().all(|&count| (1800..2200).contains(&count)));
+}
+
+#[test]
+fn test_stochastic_universal_sampling_keeps_expected_counts() {
+    // the roulette fitnesses are 0.25, 1, 0.5, 0.75 and 0 of a total of 2.5, so one draw of
+    // five parents expects 0.5, 2, 1, 1.5 and 0 of them
+    let mut rng = StdRng::seed_from_u64(11);
+    for _ in 0..20 {
+        let mut sampler = StochasticUniversalSampling.sampler(&LENGTHS);
+        let mut counts = [0; 5];
+        for _ in 0..LENGTHS.len() {
+            counts[sampler.sample(&mut rng)] += 1;
+        }
+        for (count, expected) in counts.into_iter().zip([0.5, 2.0, 1.0, 1.5, 0.0]) {
+            assert!((count as f64 - expected).abs() < 1.0 + 1e-6, "{:?}", counts);
+        }
+    }
+}
+
+#[test]
+fn test_tournament_as_big_as_the_population_picks_the_shortest_tour() {
+    let mut rng = StdRng::seed_from_u64(12);
+    for size in [LENGTHS.len(), LENGTHS.len() + 3] {
+        let mut sampler = TournamentSelection { size }.sampler(&LENGTHS);
+        for _ in 0..100 {
+            assert_eq!(sampler.sample(&mut rng), 1);
+        }
+    }
+}
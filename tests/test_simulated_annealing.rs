@@ -0,0 +1,63 @@
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rust::{
+    generator::ProblemGenerator,
+    problem::Solution,
+    simulated_annealing::{CoolingSchedule, SimulatedAnnealing},
+};
+
+/// A timer that started long enough ago to leave `budget` of a one-second limit.
+fn timer_leaving(budget: Duration) -> Instant {
+    Instant::now() - (Duration::from_secs(1) - budget)
+}
+
+#[test]
+fn test_simulated_annealing_improves_start() {
+    let num_cities = 300;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 8).generate_problem());
+    let start = Solution::from_greedy_edge(&problem, 0);
+    for (cooling_schedule, reheats) in [
+        (CoolingSchedule::Geometric, 0),
+        (CoolingSchedule::Adaptive, 0),
+        (CoolingSchedule::Geometric, 2),
+    ] {
+        let mut simulated_annealing = SimulatedAnnealing::new(Rc::clone(&problem));
+        simulated_annealing.cooling_schedule = cooling_schedule;
+        simulated_annealing.reheats = reheats;
+        let timer = timer_leaving(Duration::from_millis(300));
+        let solution = simulated_annealing.solve(&timer, 1);
+        assert!(timer.elapsed() < Duration::from_millis(1100));
+        assert!(solution.is_valid(num_cities as u32));
+        assert_eq!(solution.order_without_loop[0], 0);
+        assert!(
+            solution.total_distance() < 0.97 * start.total_distance(),
+            "{:?} with {} reheats: {} vs {}",
+            cooling_schedule,
+            reheats,
+            solution.total_distance(),
+            start.total_distance()
+        );
+    }
+}
+
+#[test]
+fn test_simulated_annealing_tiny_problems() {
+    for num_cities in 1..10 {
+        let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 3).generate_problem());
+        let solution = SimulatedAnnealing::new(problem).solve(&timer_leaving(Duration::ZERO), 1);
+        assert!(solution.is_valid(num_cities as u32));
+    }
+}
+
+#[test]
+fn test_simulated_annealing_keeps_deadline() {
+    let num_cities = 2000;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 6).generate_problem());
+    let timer = Instant::now();
+    let solution = SimulatedAnnealing::new(problem).solve(&timer, 0);
+    assert!(solution.is_valid(num_cities as u32));
+    assert!(timer.elapsed() < Duration::from_millis(500));
+}
@@ -0,0 +1,76 @@
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rust::{
+    generator::ProblemGenerator,
+    local_search::{LOCAL_SEARCH_NEIGHBORS, LocalSearch},
+    neighbors::nearest_neighbor_lists,
+    problem::{City, Problem, Solution},
+    tabu_search::TabuSearch,
+};
+
+/// A timer that started long enough ago to leave `budget` of a one-second limit.
+fn timer_leaving(budget: Duration) -> Instant {
+    Instant::now() - (Duration::from_secs(1) - budget)
+}
+
+#[test]
+fn test_tabu_search_beats_local_optimum() {
+    let num_cities = 300;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 5).generate_problem());
+    let neighbor_lists = nearest_neighbor_lists(&problem, LOCAL_SEARCH_NEIGHBORS);
+    let local_optimum = Solution::from_nearest_neighbor(&problem, 0).improve_with(
+        &[LocalSearch::TwoOpt, LocalSearch::OrOpt],
+        &neighbor_lists,
+        None,
+    );
+    let solution =
+        TabuSearch::new(Rc::clone(&problem)).solve(&timer_leaving(Duration::from_millis(300)), 1);
+    assert!(solution.is_valid(num_cities as u32));
+    assert_eq!(solution.order_without_loop[0], 0);
+    assert!(
+        solution.total_distance() < local_optimum.total_distance(),
+        "{} vs {}",
+        solution.total_distance(),
+        local_optimum.total_distance()
+    );
+}
+
+#[test]
+fn test_tabu_search_keeps_deadline() {
+    let num_cities = 2000;
+    let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 6).generate_problem());
+    let timer = Instant::now();
+    let solution = TabuSearch::new(problem).solve(&timer, 0);
+    assert!(solution.is_valid(num_cities as u32));
+    assert!(timer.elapsed() < Duration::from_millis(500));
+}
+
+#[test]
+fn test_tabu_search_tiny_problems() {
+    for num_cities in 1..20 {
+        let problem = Rc::new(ProblemGenerator::new(num_cities, 1000.0, 2).generate_problem());
+        let solution = TabuSearch::new(problem).solve(&timer_leaving(Duration::from_millis(20)), 1);
+        assert!(solution.is_valid(num_cities as u32));
+    }
+}
+
+/// `num_cities` cities on a few points, so that most of them are duplicates.
+fn duplicated_problem(num_cities: usize) -> Problem {
+    Problem::new(
+        (0..num_cities as u32)
+            .map(|i| City::new(i % 5 * 10, i % 3 * 10, 0))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_tabu_search_with_duplicate_cities() {
+    for num_cities in (3..90).step_by(7) {
+        let problem = Rc::new(duplicated_problem(num_cities));
+        let solution = TabuSearch::new(problem).solve(&timer_leaving(Duration::from_millis(20)), 1);
+        assert!(solution.is_valid(num_cities as u32));
+    }
+}
@@ -0,0 +1,119 @@
+use rust::{
+    format::TourFile,
+    json::JsonSolution,
+    problem::{City, Problem},
+    verify::{DEFAULT_RELATIVE_TOLERANCE, TourIssue, verify_json_solution, verify_tour},
+};
+
+fn square() -> Problem {
+    Problem::new(vec![
+        City::new(0, 0, 0),
+        City::new(3, 0, 0),
+        City::new(3, 4, 0),
+        City::new(0, 4, 0),
+    ])
+}
+
+fn issues(problem: &Problem, tour: &str) -> Vec<TourIssue> {
+    let tour = TourFile::from_reader(tour.as_bytes()).unwrap();
+    verify_tour(problem, &tour, DEFAULT_RELATIVE_TOLERANCE).issues
+}
+
+#[test]
+fn test_verify_valid_tour() {
+    let problem = square();
+    let tour = TourFile::from_reader("14\n3 4 0\n0 4 0\n0 0 0\n3 0 0\n3 4 0\n".as_bytes()).unwrap();
+    let report = verify_tour(&problem, &tour, DEFAULT_RELATIVE_TOLERANCE);
+    assert!(report.is_valid(), "{:?}", report.issues);
+    assert_eq!(report.actual_distance, 14.0);
+    assert_eq!(report.order, Some(vec![2, 3, 0, 1]));
+}
+
+#[test]
+fn test_verify_reports_every_issue() {
+    let problem = square();
+    let issues = issues(&problem, "10\n0 0 0\n3 0 0\n0 0 0\n9 9 9\n");
+    assert_eq!(
+        issues[..5],
+        [
+            TourIssue::NotClosed {
+                first_line: 2,
+                last_line: 5
+            },
+            TourIssue::RepeatedCity {
+                line: 4,
+                city: City::new(0, 0, 0),
+                first_line: 2
+            },
+            TourIssue::UnknownCity {
+                line: 5,
+                city: City::new(9, 9, 9)
+            },
+            TourIssue::MissingCity {
+                index: 2,
+                city: City::new(3, 4, 0)
+            },
+            TourIssue::MissingCity {
+                index: 3,
+                city: City::new(0, 4, 0)
+            },
+        ]
+    );
+    assert!(matches!(
+        issues[5..],
+        [TourIssue::DistanceMismatch { reported: 10.0, .. }]
+    ));
+}
+
+#[test]
+fn test_verify_distance_mismatch() {
+    let problem = square();
+    assert_eq!(
+        issues(&problem, "15\n0 0 0\n3 0 0\n3 4 0\n0 4 0\n0 0 0\n"),
+        vec![TourIssue::DistanceMismatch {
+            reported: 15.0,
+            actual: 14.0
+        }]
+    );
+}
+
+#[test]
+fn test_verify_duplicate_coordinates() {
+    let problem = Problem::new(vec![
+        City::new(0, 0, 0),
+        City::new(1, 0, 0),
+        City::new(0, 0, 0),
+    ]);
+    let tour = TourFile::from_reader("2\n0 0 0\n1 0 0\n0 0 0\n0 0 0\n".as_bytes()).unwrap();
+    let report = verify_tour(&problem, &tour, DEFAULT_RELATIVE_TOLERANCE);
+    assert!(report.is_valid(), "{:?}", report.issues);
+    assert_eq!(report.order, Some(vec![0, 1, 2]));
+}
+
+#[test]
+fn test_verify_json_solution() {
+    let problem = square();
+    let json = |order: Vec<u32>, total_distance| JsonSolution {
+        order,
+        total_distance,
+        solver: None,
+        seed: None,
+        elapsed_secs: None,
+        gap: None,
+    };
+    let report = verify_json_solution(&problem, &json(vec![2, 3, 0, 1], 14.0), 1e-5);
+    assert!(report.is_valid(), "{:?}", report.issues);
+    assert_eq!(report.order, Some(vec![2, 3, 0, 1]));
+    let report = verify_json_solution(&problem, &json(vec![2, 7, 0, 1], 14.0), 1e-5);
+    assert_eq!(report.order, None);
+    assert_eq!(
+        report.issues[..2],
+        [
+            TourIssue::IndexOutOfRange { line: 2, index: 7 },
+            TourIssue::MissingCity {
+                index: 3,
+                city: City::new(0, 4, 0)
+            },
+        ]
+    );
+}
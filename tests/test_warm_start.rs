@@ -0,0 +1,73 @@
+use std::{rc::Rc, time::Instant};
+
+use rust::{
+    generator::ProblemGenerator,
+    genetic::GeneticAlgorithm,
+    problem::Solution,
+    verify::TourIssue,
+    warm_start::{TourFormat, WarmStartError, load_tour},
+};
+
+#[test]
+fn test_load_tour_round_trip() {
+    let problem = Rc::new(ProblemGenerator::new(30, 1000.0, 7).generate_problem());
+    let solution = Solution::from_nearest_neighbor(&problem, 3);
+    let mut written = Vec::new();
+    solution.write_to(&mut written).unwrap();
+    for format in [TourFormat::Auto, TourFormat::Coordinates] {
+        let loaded = load_tour(&problem, written.as_slice(), format).unwrap();
+        assert_eq!(loaded.order_without_loop, solution.order_without_loop);
+    }
+
+    let indices = solution
+        .order_without_loop
+        .iter()
+        .map(|index| format!("{}\n", index))
+        .collect::<String>();
+    for format in [TourFormat::Auto, TourFormat::Indices] {
+        let loaded = load_tour(&problem, indices.as_bytes(), format).unwrap();
+        assert_eq!(loaded.order_without_loop, solution.order_without_loop);
+        assert_eq!(loaded.total_distance(), solution.total_distance());
+    }
+}
+
+#[test]
+fn test_load_index_tour_errors() {
+    let problem = Rc::new(ProblemGenerator::new(3, 1000.0, 7).generate_problem());
+    let Err(WarmStartError::InvalidTour(issues)) =
+        load_tour(&problem, "0\n5\n0\n".as_bytes(), TourFormat::Indices)
+    else {
+        panic!("tour should be rejected");
+    };
+    assert_eq!(
+        issues,
+        vec![
+            TourIssue::IndexOutOfRange { line: 2, index: 5 },
+            TourIssue::RepeatedCity {
+                line: 3,
+                city: problem.cities[0],
+                first_line: 1
+            },
+            TourIssue::MissingCity {
+                index: 1,
+                city: problem.cities[1]
+            },
+            TourIssue::MissingCity {
+                index: 2,
+                city: problem.cities[2]
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_genetic_algorithm_keeps_initial_solution() {
+    let problem = Rc::new(ProblemGenerator::new(20, 1000.0, 7).generate_problem());
+    let order = Solution::from_nearest_neighbor(&problem, 5).order_without_loop;
+    let initial_solution = Solution::new(order.clone(), Rc::downgrade(&problem), None);
+    let mut genetic_algorithm = GeneticAlgorithm::new(problem.clone(), 20, 20);
+    genetic_algorithm.initial_solutions.push(initial_solution);
+    // with no time at all, the best solution is the one from the initial population
+    let solution = genetic_algorithm.solve(&Instant::now(), 0);
+    assert_eq!(solution.order_without_loop, order);
+}